@@ -0,0 +1,236 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use casper_macros::CasperABI;
+use casper_sdk::casper::Entity;
+
+use crate::types::{
+    BurnMode, EventsMode, HashAlgorithm, MetadataMutability, MintingMode, NFTIdentifierMode,
+    NFTKind, NFTMetadataKind, OwnershipMode, Royalty, TokenLockMode, WhitelistMode,
+};
+
+/// Everything the constructor needs to install a collection. Start from
+/// [`InstallConfig::builder`] and only override what differs from the defaults.
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone)]
+pub struct InstallConfig {
+    pub collection_name: String,
+    pub collection_symbol: String,
+    pub total_token_supply: Option<u64>,
+    pub allow_minting: bool,
+    pub minting_mode: MintingMode,
+    pub ownership_mode: OwnershipMode,
+    pub nft_kind: NFTKind,
+    pub whitelist_mode: WhitelistMode,
+    pub acl_whitelist: Vec<Entity>,
+    pub acl_package_mode: bool,
+    pub package_operator_mode: bool,
+    pub package_hash: String,
+    pub base_metadata_kind: NFTMetadataKind,
+    pub optional_metadata: Vec<u8>,
+    pub additional_required_metadata: Vec<u8>,
+    pub identifier_mode: NFTIdentifierMode,
+    pub metadata_mutability: MetadataMutability,
+    pub burn_mode: BurnMode,
+    pub operator_burn_mode: bool,
+    pub events_mode: EventsMode,
+    pub transfer_filter_contract: Option<Entity>,
+    pub json_schema: Option<String>,
+    pub token_lock_mode: TokenLockMode,
+    pub default_royalty: Option<Royalty>,
+    pub chain_name: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub allow_migration: bool,
+}
+
+impl Default for InstallConfig {
+    fn default() -> Self {
+        Self {
+            collection_name: "test-collection".into(),
+            collection_symbol: "tc".into(),
+            total_token_supply: Some(100),
+            allow_minting: true,
+            minting_mode: MintingMode::Installer,
+            ownership_mode: OwnershipMode::Transferable,
+            nft_kind: NFTKind::Virtual,
+            whitelist_mode: WhitelistMode::Unlocked,
+            acl_whitelist: Vec::new(),
+            acl_package_mode: false,
+            package_operator_mode: false,
+            package_hash: "".into(),
+            base_metadata_kind: NFTMetadataKind::CEP78,
+            optional_metadata: Vec::new(),
+            additional_required_metadata: Vec::new(),
+            identifier_mode: NFTIdentifierMode::Ordinal,
+            metadata_mutability: MetadataMutability::Immutable,
+            burn_mode: BurnMode::Burnable,
+            operator_burn_mode: false,
+            events_mode: EventsMode::NoEvents,
+            transfer_filter_contract: None,
+            json_schema: None,
+            token_lock_mode: TokenLockMode::Admin,
+            default_royalty: None,
+            chain_name: "casper".into(),
+            hash_algorithm: HashAlgorithm::Blake2b256,
+            allow_migration: false,
+        }
+    }
+}
+
+impl InstallConfig {
+    pub fn builder() -> InstallConfigBuilder {
+        InstallConfigBuilder::default()
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct InstallConfigBuilder {
+    config: InstallConfig,
+}
+
+impl InstallConfigBuilder {
+    pub fn collection_name(mut self, collection_name: impl Into<String>) -> Self {
+        self.config.collection_name = collection_name.into();
+        self
+    }
+
+    pub fn collection_symbol(mut self, collection_symbol: impl Into<String>) -> Self {
+        self.config.collection_symbol = collection_symbol.into();
+        self
+    }
+
+    pub fn total_token_supply(mut self, total_token_supply: u64) -> Self {
+        self.config.total_token_supply = Some(total_token_supply);
+        self
+    }
+
+    /// Open edition: tokens can be minted without a supply cap.
+    pub fn unlimited_supply(mut self) -> Self {
+        self.config.total_token_supply = None;
+        self
+    }
+
+    pub fn allow_minting(mut self, allow_minting: bool) -> Self {
+        self.config.allow_minting = allow_minting;
+        self
+    }
+
+    pub fn minting_mode(mut self, minting_mode: MintingMode) -> Self {
+        self.config.minting_mode = minting_mode;
+        self
+    }
+
+    pub fn ownership_mode(mut self, ownership_mode: OwnershipMode) -> Self {
+        self.config.ownership_mode = ownership_mode;
+        self
+    }
+
+    pub fn nft_kind(mut self, nft_kind: NFTKind) -> Self {
+        self.config.nft_kind = nft_kind;
+        self
+    }
+
+    pub fn whitelist_mode(mut self, whitelist_mode: WhitelistMode) -> Self {
+        self.config.whitelist_mode = whitelist_mode;
+        self
+    }
+
+    pub fn acl_whitelist(mut self, acl_whitelist: Vec<Entity>) -> Self {
+        self.config.acl_whitelist = acl_whitelist;
+        self
+    }
+
+    pub fn acl_package_mode(mut self, acl_package_mode: bool) -> Self {
+        self.config.acl_package_mode = acl_package_mode;
+        self
+    }
+
+    pub fn package_operator_mode(mut self, package_operator_mode: bool) -> Self {
+        self.config.package_operator_mode = package_operator_mode;
+        self
+    }
+
+    pub fn package_hash(mut self, package_hash: impl Into<String>) -> Self {
+        self.config.package_hash = package_hash.into();
+        self
+    }
+
+    pub fn base_metadata_kind(mut self, base_metadata_kind: NFTMetadataKind) -> Self {
+        self.config.base_metadata_kind = base_metadata_kind;
+        self
+    }
+
+    pub fn optional_metadata(mut self, optional_metadata: Vec<u8>) -> Self {
+        self.config.optional_metadata = optional_metadata;
+        self
+    }
+
+    pub fn additional_required_metadata(mut self, additional_required_metadata: Vec<u8>) -> Self {
+        self.config.additional_required_metadata = additional_required_metadata;
+        self
+    }
+
+    pub fn identifier_mode(mut self, identifier_mode: NFTIdentifierMode) -> Self {
+        self.config.identifier_mode = identifier_mode;
+        self
+    }
+
+    pub fn metadata_mutability(mut self, metadata_mutability: MetadataMutability) -> Self {
+        self.config.metadata_mutability = metadata_mutability;
+        self
+    }
+
+    pub fn burn_mode(mut self, burn_mode: BurnMode) -> Self {
+        self.config.burn_mode = burn_mode;
+        self
+    }
+
+    pub fn operator_burn_mode(mut self, operator_burn_mode: bool) -> Self {
+        self.config.operator_burn_mode = operator_burn_mode;
+        self
+    }
+
+    pub fn events_mode(mut self, events_mode: EventsMode) -> Self {
+        self.config.events_mode = events_mode;
+        self
+    }
+
+    pub fn transfer_filter_contract(mut self, transfer_filter_contract: Entity) -> Self {
+        self.config.transfer_filter_contract = Some(transfer_filter_contract);
+        self
+    }
+
+    pub fn json_schema(mut self, json_schema: impl Into<String>) -> Self {
+        self.config.json_schema = Some(json_schema.into());
+        self
+    }
+
+    pub fn token_lock_mode(mut self, token_lock_mode: TokenLockMode) -> Self {
+        self.config.token_lock_mode = token_lock_mode;
+        self
+    }
+
+    pub fn default_royalty(mut self, receiver: Entity, basis_points: u16) -> Self {
+        self.config.default_royalty = Some(Royalty {
+            receiver,
+            basis_points,
+        });
+        self
+    }
+
+    pub fn chain_name(mut self, chain_name: impl Into<String>) -> Self {
+        self.config.chain_name = chain_name.into();
+        self
+    }
+
+    pub fn hash_algorithm(mut self, hash_algorithm: HashAlgorithm) -> Self {
+        self.config.hash_algorithm = hash_algorithm;
+        self
+    }
+
+    pub fn allow_migration(mut self, allow_migration: bool) -> Self {
+        self.config.allow_migration = allow_migration;
+        self
+    }
+
+    pub fn build(self) -> InstallConfig {
+        self.config
+    }
+}
@@ -1,20 +1,27 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::{
+    config::InstallConfig,
     error::NFTCoreError,
     events::{
         events_cep47::CEP47Event,
         events_ces::{
-            Approval, ApprovalForAll, ApprovalRevoked, Burn, Event, Mint, RevokedForAll, Transfer,
-            VariablesSet,
+            AdminRenounced, AdminTransferStarted, AdminTransferred, Approval, ApprovalForAll,
+            ApprovalRevoked, Burn, Event, MetadataUpdated, Migration, Mint, Paused, RevokedForAll,
+            RoleGranted, RoleRevoked, TokenLocked, TokenRecovered, TokenUnlocked, Transfer,
+            Unpaused, UpdateUser, VariablesSet,
         },
     },
+    receiver::CEP78ReceiverRef,
+    transfer_filter::CEP78TransferFilterRef,
     types::*,
 };
-use blake2b_simd::blake2b;
+use blake2b_simd::Params;
+use casper::Entity;
 use casper_macros::*;
 use casper_sdk::*;
-use casper::Entity;
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
 use types::*;
 
 #[casper(contract_state)]
@@ -24,31 +31,7 @@ pub struct NFTContract {
 
 impl Default for NFTContract {
     fn default() -> Self {
-        let state = CEP78State {
-            collection_name: "test-collection".into(),
-            collection_symbol: "tc".into(),
-            total_token_supply: 100,
-            allow_minting: true,
-            minting_mode: MintingMode::Installer,
-            ownership_mode: OwnershipMode::Transferable,
-            nft_kind: NFTKind::Virtual,
-            whitelist_mode: WhitelistMode::Unlocked,
-            acl_whitelist: Vec::new(),
-            acl_package_mode: false,
-            package_operator_mode: false,
-            package_hash: "".into(),
-            base_metadata_kind: NFTMetadataKind::CEP78,
-            optional_metadata: Vec::new(),
-            additional_required_metadata: Vec::new(),
-            identifier_mode: NFTIdentifierMode::Ordinal,
-            metadata_mutability: MetadataMutability::Immutable,
-            burn_mode: BurnMode::Burnable,
-            operator_burn_mode: false,
-            installer: Entity::Account([0; 32]),
-            events_mode: EventsMode::NoEvents,
-            minted_tokens_count: 0,
-            store: Default::default(),
-        };
+        let state = Self::state_from_config(InstallConfig::default(), Entity::Account([0; 32]));
 
         Self { state }
     }
@@ -57,58 +40,12 @@ impl Default for NFTContract {
 #[casper(contract)]
 impl NFTContract {
     #[casper(constructor)]
-    pub fn new(
-        collection_name: String,
-        collection_symbol: String,
-        total_token_supply: u64,
-        allow_minting: bool,
-        minting_mode: MintingMode,
-        ownership_mode: OwnershipMode,
-        nft_kind: NFTKind,
-        whitelist_mode: WhitelistMode,
-        acl_whitelist: Vec<Address>,
-        acl_package_mode: bool,
-        package_operator_mode: bool,
-        package_hash: String,
-        base_metadata_kind: NFTMetadataKind,
-        optional_metadata: Vec<u8>,
-        additional_required_metadata: Vec<u8>,
-        identifier_mode: NFTIdentifierMode,
-        metadata_mutability: MetadataMutability,
-        burn_mode: BurnMode,
-        operator_burn_mode: bool,
-        events_mode: Option<EventsMode>,
-    ) -> NFTContract {
+    pub fn new(config: InstallConfig) -> NFTContract {
+        // Reject inconsistent configurations before anything gets installed.
+        Self::check_install_configuration(&config).unwrap_or_revert();
+
         let installer = casper::get_caller();
-        let events_mode = events_mode.unwrap_or(EventsMode::NoEvents);
-        let minted_tokens_count = 0u64;
-        let store = StateStore::default();
-
-        let state = CEP78State {
-            collection_name,
-            collection_symbol,
-            total_token_supply,
-            allow_minting,
-            minting_mode,
-            ownership_mode,
-            nft_kind,
-            whitelist_mode,
-            acl_whitelist,
-            acl_package_mode,
-            package_operator_mode,
-            package_hash,
-            base_metadata_kind,
-            optional_metadata,
-            additional_required_metadata,
-            identifier_mode,
-            metadata_mutability,
-            burn_mode,
-            operator_burn_mode,
-            minted_tokens_count,
-            events_mode,
-            installer,
-            store,
-        };
+        let state = Self::state_from_config(config, installer);
 
         Self { state }
     }
@@ -121,12 +58,18 @@ impl NFTContract {
         operator_burn_mode: Option<bool>,
         acl_whitelist: Option<Vec<Entity>>,
         contract_whitelist: Option<Vec<Entity>>,
+        total_token_supply: Option<u64>,
     ) -> Result<(), NFTCoreError> {
-        // Only the installing account can change the mutable variables.
-        if self.state.installer != casper::get_caller() {
+        // Only admins can change the mutable variables.
+        if !self.holds_role(casper::get_caller(), Role::Admin) {
             return Err(NFTCoreError::InvalidAccount);
         }
 
+        // Closing an edition early lowers the cap, it can never be raised again.
+        if let Some(total_token_supply) = total_token_supply {
+            self.update_total_token_supply(total_token_supply)?;
+        }
+
         if let Some(allow_minting) = allow_minting {
             self.state.allow_minting = allow_minting;
         }
@@ -155,7 +98,10 @@ impl NFTContract {
         if !new_acl_whitelist.is_empty() {
             match &self.state.whitelist_mode {
                 WhitelistMode::Unlocked => {
-                    self.state.acl_whitelist.clear();
+                    // Entities left out of the new whitelist lose their minting rights.
+                    for key in self.state.acl_whitelist.clone() {
+                        self.insert_acl_entry(key, false);
+                    }
                     for key in new_acl_whitelist {
                         self.insert_acl_entry(key, true);
                     }
@@ -173,333 +119,901 @@ impl NFTContract {
         Ok(())
     }
 
-    // Mints a new token. Minting will fail if allow_minting is set to false.
-    pub fn mint(
-        &mut self,
-        token_metadata: String,
-        token_owner: Entity,
-        optional_token_hash: Option<String>,
-    ) -> Result<TokenIdentifier, NFTCoreError> {
-        // The contract owner can toggle the minting behavior on and off over time.
-        // The contract is toggled on by default.
-        // If contract minting behavior is currently toggled off we revert.
-        if !self.state.allow_minting {
-            return Err(NFTCoreError::MintingIsPaused);
-        }
+    // Called after the package received new code. Brings the stored state up to the layout of
+    // this release and optionally lowers the total token supply on the way. This entrypoint
+    // doesn't take `self`: state written by an older release can't be decoded as the current
+    // layout, so it is read and converted here instead.
+    pub fn upgrade(total_token_supply: Option<u64>) -> Result<(), NFTCoreError> {
+        // Newer layouts and unknown versions fail to decode.
+        let stored_state = casper::read_state::<StoredState>()
+            .map_err(|_| NFTCoreError::InvalidCheckForUpgrade)?;
+        let state = match stored_state {
+            StoredState::Baseline(state) => CEP78State::from(state),
+            StoredState::Current(state) => state,
+        };
+        let mut contract = Self { state };
 
-        let total_token_supply = self.state.total_token_supply;
-        let minted_tokens_count = self.state.minted_tokens_count;
+        if !contract.holds_role(casper::get_caller(), Role::Admin) {
+            return Err(NFTCoreError::InvalidAccount);
+        }
 
-        // Revert if the token supply has been exhausted.
-        if minted_tokens_count >= total_token_supply {
-            return Err(NFTCoreError::TokenSupplyDepleted);
+        // Only collections installed as part of a package can be upgraded.
+        if contract.state.package_hash.is_empty() {
+            return Err(NFTCoreError::MissingPackageHashForUpgrade);
         }
 
-        let caller = casper::get_caller();
+        if let Some(total_token_supply) = total_token_supply {
+            contract.update_total_token_supply(total_token_supply)?;
 
-        // Revert if minting is private and caller is not installer.
-        if MintingMode::Installer == self.state.minting_mode {
-            if self.state.installer != caller {
-                return Err(NFTCoreError::InvalidMinter);
+            match contract.state.events_mode {
+                EventsMode::NoEvents => {}
+                EventsMode::CES => contract.emit_ces_event(VariablesSet::new()),
+                EventsMode::CEP47 => contract.write_cep47_event(CEP47Event::VariablesSet),
             }
         }
 
-        // Revert if minting is acl and caller is not whitelisted.
-        if MintingMode::Acl == self.state.minting_mode {
-            if !self.is_whitelisted(caller) {
-                return Err(NFTCoreError::InvalidMinter);
-            }
-        }
+        casper::write_state(&contract).unwrap_or_revert();
 
-        let token_identifier = match self.state.identifier_mode {
-            NFTIdentifierMode::Ordinal => TokenIdentifier::Ordinal(minted_tokens_count),
-            NFTIdentifierMode::Hash => TokenIdentifier::Hash(match optional_token_hash {
-                Some(hash) => hash,
-                None => self.generate_hash(token_metadata.clone()),
-            }),
-        };
+        Ok(())
+    }
 
-        // TODO: Add metadata validation support
-        // let metadata_kinds: Vec<(NFTMetadataKind, bool)> = Vec::new();
-        // for (metadata_kind, required) in metadata_kinds {
-        //     if !required {
-        //         continue;
-        //     }
-        //     let token_metadata_validation = self.validate_metadata(metadata_kind, token_metadata.clone());
-        //     match token_metadata_validation {
-        //         Ok(validated_token_metadata) => self.insert_metadata(
-        //             &token_identifier,
-        //             &validated_token_metadata
-        //         ),
-        //         Err(err) => {
-        //             return Err(err);
-        //         }
-        //     }
-        // }
+    pub fn state_version(&self) -> Result<u32, NFTCoreError> {
+        Ok(self.state.version)
+    }
 
-        // The contract's ownership behavior (determined at installation) determines,
-        // who owns the NFT we are about to mint.()
-        self.insert_token_owner(&token_identifier, token_owner);
-        self.insert_token_issuer(&token_identifier, token_owner);
+    // Imports a batch of tokens exported from a VM1 CEP-78 contract. Batches have to be imported
+    // in minting order, and the whole batch is validated before any token is written. Once every
+    // batch is in, `finalize_migration` locks the migration for good.
+    pub fn migrate(&mut self, records: Vec<MigrationRecord>) -> Result<(), NFTCoreError> {
+        self.check_can_migrate()?;
 
-        // Update the forward and reverse trackers
-        if NFTIdentifierMode::Hash == self.state.identifier_mode {
-            if let Err(e) = self.insert_hash_id_lookups(&token_identifier) {
-                return Err(e);
-            }
+        if self
+            .remaining_supply()
+            .is_some_and(|remaining_supply| records.len() as u64 > remaining_supply)
+        {
+            return Err(NFTCoreError::TokenSupplyDepleted);
         }
 
-        // Increment the count of owned tokens.
-        let current_balance = self.get_token_balance(token_owner);
-        self.set_token_balance(token_owner, current_balance + 1);
+        let first_index = self.state.minted_tokens_count;
+        let mut seen_hashes = BTreeSet::new();
 
-        // Increment number_of_minted_tokens by one
-        self.state.minted_tokens_count += 1;
+        for (offset, record) in records.iter().enumerate() {
+            if record.owner.is_none() {
+                return Err(NFTCoreError::UnregisteredOwnerFromMigration);
+            }
 
-        // Emit mint event
-        match self.state.events_mode {
-            EventsMode::NoEvents => {}
-            EventsMode::CES => self.emit_ces_event(Mint::new(
-                Self::unwrap_entity(token_owner),
-                token_identifier.clone(),
-                token_metadata,
-            )),
-            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::Mint {
-                recipient: Self::unwrap_entity(token_owner),
-                token_id: token_identifier.clone(),
-            }),
+            match &record.token_identifier {
+                // Ordinals have to continue exactly where the previous batch stopped.
+                TokenIdentifier::Ordinal(index) => {
+                    if NFTIdentifierMode::Ordinal != self.state.identifier_mode
+                        || *index != first_index + offset as u64
+                    {
+                        return Err(NFTCoreError::InvalidTokenIdentifier);
+                    }
+                }
+                TokenIdentifier::Hash(hash) => {
+                    if NFTIdentifierMode::Hash != self.state.identifier_mode
+                        || Self::check_token_hash(hash).is_err()
+                    {
+                        return Err(NFTCoreError::InvalidTokenIdentifier);
+                    }
+                    if !seen_hashes.insert(hash.clone()) {
+                        return Err(NFTCoreError::DuplicateIdentifier);
+                    }
+                    self.check_identifier_available(&record.token_identifier)?;
+                }
+            }
         }
 
-        Ok(token_identifier)
-    }
+        // Aggregate the balance changes so that every owner is only written once.
+        let mut balance_increments: Vec<(Entity, u64)> = Vec::new();
 
-    // Marks token as burnt. This blocks any future call to transfer token.
-    pub fn burn(&mut self, token_identifier: TokenIdentifier) -> Result<(), NFTCoreError> {
-        if let BurnMode::NonBurnable = self.state.burn_mode {
-            return Err(NFTCoreError::InvalidBurnMode);
-        }
+        for record in records {
+            let Some(owner) = record.owner else {
+                return Err(NFTCoreError::UnregisteredOwnerFromMigration);
+            };
+            let issuer = record.issuer.unwrap_or(owner);
 
-        let caller = casper::get_caller();
-        let Some(token_owner) = self.read_token_owner(&token_identifier) else {
-            return Err(NFTCoreError::MissingTokenOwner);
-        };
+            self.write_minted_token(&record.token_identifier, owner, issuer, &record.metadata)?;
 
-        // Check if caller is owner
-        let is_owner = token_owner == caller;
+            if let Some(approved) = record.approved {
+                self.set_approved(&record.token_identifier, approved, None)?;
+            }
 
-        // Check if caller is operator to execute burn
-        // With operator package mode check if caller's package is operator to let contract execute burn
-        let is_operator = if !is_owner {
-            self.read_operator(token_owner, caller)
-        } else {
-            false
-        };
+            // Burnt tokens stay with their owner but don't count towards the balance.
+            if record.burned {
+                self.set_token_burned(record.token_identifier);
+                continue;
+            }
 
-        // Revert if caller is not token_owner nor operator for the owner
-        if !is_owner && !is_operator {
-            return Err(NFTCoreError::InvalidTokenOwner);
+            match balance_increments
+                .iter_mut()
+                .find(|(entity, _)| *entity == owner)
+            {
+                Some((_, increment)) => *increment += 1,
+                None => balance_increments.push((owner, 1)),
+            }
         }
 
-        // It makes sense to keep this token as owned by the caller. It just happens that the caller
-        // owns a burnt token. That's all. Similarly, we should probably also not change the
-        // owned_tokens dictionary.
-        if self.read_token_burned(&token_identifier) {
-            return Err(NFTCoreError::PreviouslyBurntToken);
+        for (owner, increment) in balance_increments {
+            let current_balance = self.get_token_balance(owner);
+            self.set_token_balance(owner, current_balance + increment);
         }
 
-        self.set_token_burned(token_identifier.clone());
+        Ok(())
+    }
 
-        let balance = self.get_token_balance(token_owner);
-        let updated_balance = match balance {
-            0 => return Err(NFTCoreError::FatalTokenIdDuplication),
-            _ => {
-                if balance > 0u64 {
-                    balance - 1u64
-                } else {
-                    return Err(NFTCoreError::FatalTokenIdDuplication);
-                }
-            }
-        };
+    // Ends the migration. Afterwards `migrate` can't be called anymore and minting resumes.
+    pub fn finalize_migration(&mut self) -> Result<(), NFTCoreError> {
+        self.check_can_migrate()?;
 
-        self.set_token_balance(token_owner, updated_balance);
+        self.state.migration_finalized = true;
 
-        // Emit Burn event
         match self.state.events_mode {
             EventsMode::NoEvents => {}
-            EventsMode::CES => {
-                self.emit_ces_event(Burn::new(token_owner, token_identifier, caller))
-            }
-            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::Burn {
-                owner: token_owner,
-                token_id: token_identifier,
-                burner: caller,
-            }),
-        };
+            EventsMode::CES => self.emit_ces_event(Migration::new()),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::Migrate),
+        }
 
         Ok(())
     }
 
-    pub fn approve(
-        &mut self,
-        operator: Option<Entity>,
-        spender: Entity,
-        token_identifier: TokenIdentifier,
-    ) -> Result<(), NFTCoreError> {
-        // If we are in minter or assigned mode it makes no sense to approve an account. Hence we
-        // revert.
-        if let OwnershipMode::Minter | OwnershipMode::Assigned = self.state.ownership_mode {
-            return Err(NFTCoreError::InvalidOwnershipMode);
+    // Starts handing the installer role over to `new_admin`. The transfer only takes effect once
+    // `new_admin` calls `accept_admin`, until then it can be overridden by another call.
+    pub fn transfer_admin(&mut self, new_admin: Entity) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        if !self.is_installer(caller) {
+            return Err(NFTCoreError::InvalidAccount);
         }
 
-        let caller = casper::get_caller();
+        self.state.pending_installer = Some(new_admin);
 
-        let number_of_minted_tokens = self.state.minted_tokens_count;
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => self.emit_ces_event(AdminTransferStarted::new(caller, new_admin)),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::AdminTransferStarted {
+                current_admin: caller,
+                pending_admin: new_admin,
+            }),
+        }
 
-        if let NFTIdentifierMode::Ordinal = self.state.identifier_mode {
-            // Revert if token_id is out of bounds
-            if let TokenIdentifier::Ordinal(index) = &token_identifier {
-                if *index >= number_of_minted_tokens {
-                    return Err(NFTCoreError::InvalidTokenIdentifier);
-                }
-            }
+        Ok(())
+    }
+
+    // Completes a transfer started with `transfer_admin`. Only the pending admin can accept.
+    pub fn accept_admin(&mut self) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        if self.state.pending_installer != Some(caller) {
+            return Err(NFTCoreError::InvalidAccount);
         }
 
-        let Some(owner) = self.read_token_owner(&token_identifier) else {
-            return Err(NFTCoreError::MissingTokenOwner);
+        let Some(previous_admin) = self.state.installer else {
+            return Err(NFTCoreError::MissingInstaller);
         };
 
-        // Revert if caller is not token owner nor operator.
-        // Only the token owner or an operator can approve an account
-        let is_owner = caller == owner;
-        let is_operator = !is_owner && self.read_operator(owner, caller);
-
-        if !is_owner && !is_operator {
-            return Err(NFTCoreError::InvalidTokenOwner);
-        }
+        self.state.installer = Some(caller);
+        self.state.pending_installer = None;
 
-        // We assume a burnt token cannot be approved
-        if self.read_token_burned(&token_identifier) {
-            return Err(NFTCoreError::PreviouslyBurntToken);
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => self.emit_ces_event(AdminTransferred::new(previous_admin, caller)),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::AdminTransferred {
+                previous_admin,
+                new_admin: caller,
+            }),
         }
 
-        let spender = match operator {
-            None => spender,
-            // Deprecated in favor of spender
-            Some(operator) => operator,
-        };
+        Ok(())
+    }
 
-        // If token owner or operator tries to approve itself that's probably a mistake and we revert.
-        if caller == spender {
+    // Gives up the installer role for good, this can't be undone. Entities granted `Role::Admin`
+    // keep their admin powers, revoke those grants first to leave the collection without admin.
+    pub fn renounce_admin(&mut self) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        if !self.is_installer(caller) {
             return Err(NFTCoreError::InvalidAccount);
         }
 
-        if let Err(e) = self.set_approved(&token_identifier, spender) {
-            return Err(e);
-        }
+        self.state.installer = None;
+        self.state.pending_installer = None;
 
-        // Emit Approval event.
-        let owner = Self::unwrap_entity(owner);
-        let spender = Self::unwrap_entity(spender);
         match self.state.events_mode {
             EventsMode::NoEvents => {}
-            EventsMode::CES => self.emit_ces_event(Approval::new(owner, spender, token_identifier)),
-            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::ApprovalGranted {
-                owner,
-                spender,
-                token_id: token_identifier,
+            EventsMode::CES => self.emit_ces_event(AdminRenounced::new(caller)),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::AdminRenounced {
+                previous_admin: caller,
             }),
-        };
+        }
 
         Ok(())
     }
 
-    // Revokes an account as approved for an identified token transfer
-    pub fn revoke(&mut self, token_identifier: TokenIdentifier) -> Result<(), NFTCoreError> {
-        // If we are in minter or assigned mode it makes no sense to approve an account. Hence we
-        // revert.
-        if let OwnershipMode::Minter | OwnershipMode::Assigned = self.state.ownership_mode {
-            return Err(NFTCoreError::InvalidOwnershipMode);
-        }
+    pub fn admin(&self) -> Result<Option<Entity>, NFTCoreError> {
+        Ok(self.state.installer)
+    }
 
-        let caller = casper::get_caller();
+    // Pauses transfers, approvals and burns until `unpause` is called. Minting is controlled
+    // separately through `allow_minting`.
+    pub fn pause(&mut self) -> Result<(), NFTCoreError> {
+        self.set_paused(true)
+    }
 
-        let number_of_minted_tokens = self.state.minted_tokens_count;
+    pub fn unpause(&mut self) -> Result<(), NFTCoreError> {
+        self.set_paused(false)
+    }
 
-        if let NFTIdentifierMode::Ordinal = self.state.identifier_mode {
-            // Revert if token_id is out of bounds
-            if let TokenIdentifier::Ordinal(index) = &token_identifier {
-                if *index >= number_of_minted_tokens {
-                    return Err(NFTCoreError::InvalidTokenIdentifier);
-                }
-            }
-        }
+    pub fn is_paused(&self) -> Result<bool, NFTCoreError> {
+        Ok(self.state.paused)
+    }
 
-        let Some(owner) = self.read_token_owner(&token_identifier) else {
-            return Err(NFTCoreError::MissingTokenOwner);
-        };
+    // Grants `role` to `entity`. Only admins can grant roles.
+    pub fn grant_role(&mut self, role: Role, entity: Entity) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        if !self.holds_role(caller, Role::Admin) {
+            return Err(NFTCoreError::InvalidAccount);
+        }
 
-        // Revert if caller is not token owner nor operator.
-        // Only the token owner or an operator can approve an account
-        let is_owner = caller == owner;
-        let is_operator = !is_owner && self.read_operator(owner, caller);
+        let mut data = self
+            .state
+            .store
+            .entity_data
+            .get(&entity)
+            .unwrap_or_default();
+        if !data.roles.contains(&role) {
+            data.roles.push(role);
+            self.state.store.entity_data.insert(&entity, &data);
+        }
 
-        if !is_owner && !is_operator {
-            return Err(NFTCoreError::InvalidTokenOwner);
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => self.emit_ces_event(RoleGranted::new(role, entity, caller)),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::RoleGranted {
+                role,
+                account: entity,
+                sender: caller,
+            }),
         }
 
-        // We assume a burnt token cannot be approved
-        if self.read_token_burned(&token_identifier) {
-            return Err(NFTCoreError::PreviouslyBurntToken);
+        Ok(())
+    }
+
+    // Revokes `role` from `entity`. Only admins can revoke roles. The installer's implicit roles
+    // can't be revoked, use `transfer_admin` or `renounce_admin` instead.
+    pub fn revoke_role(&mut self, role: Role, entity: Entity) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        if !self.holds_role(caller, Role::Admin) {
+            return Err(NFTCoreError::InvalidAccount);
         }
 
-        if let Err(e) = self.clear_approved(&token_identifier) {
-            return Err(e);
+        if let Some(mut data) = self.state.store.entity_data.get(&entity) {
+            data.roles.retain(|granted| *granted != role);
+            self.state.store.entity_data.insert(&entity, &data);
         }
 
-        let owner = Self::unwrap_entity(owner);
         match self.state.events_mode {
             EventsMode::NoEvents => {}
-            EventsMode::CES => self.emit_ces_event(ApprovalRevoked::new(owner, token_identifier)),
-            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::ApprovalRevoked {
-                owner,
-                token_id: token_identifier,
+            EventsMode::CES => self.emit_ces_event(RoleRevoked::new(role, entity, caller)),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::RoleRevoked {
+                role,
+                account: entity,
+                sender: caller,
             }),
-        };
+        }
 
         Ok(())
     }
 
-    // Approves the specified operator for transfer of owner's tokens.
-    pub fn set_approval_for_all(
+    pub fn has_role(&self, role: Role, entity: Entity) -> Result<bool, NFTCoreError> {
+        Ok(self.holds_role(entity, role))
+    }
+
+    // Mints a new token. Minting will fail if allow_minting is set to false.
+    pub fn mint(
         &mut self,
-        approve_all: bool,
-        operator: Entity,
-    ) -> Result<(), NFTCoreError> {
-        // If we are in minter or assigned mode it makes no sense to approve an operator. Hence we
-        // revert.
-        if let OwnershipMode::Minter | OwnershipMode::Assigned = self.state.ownership_mode {
-            return Err(NFTCoreError::InvalidOwnershipMode);
-        }
+        token_metadata: String,
+        token_owner: Entity,
+        optional_token_hash: Option<String>,
+    ) -> Result<TokenIdentifier, NFTCoreError> {
+        self.mint_with_royalty(token_metadata, token_owner, optional_token_hash, None)
+    }
 
+    // Mints a new token like `mint`, overriding the collection royalty for this token.
+    pub fn mint_with_royalty(
+        &mut self,
+        token_metadata: String,
+        token_owner: Entity,
+        optional_token_hash: Option<String>,
+        royalty: Option<Royalty>,
+    ) -> Result<TokenIdentifier, NFTCoreError> {
         let caller = casper::get_caller();
-
-        // If caller tries to approve itself as operator that's probably a mistake and we revert.
-        if caller == operator {
-            return Err(NFTCoreError::InvalidAccount);
+        self.check_can_mint(caller, 1)?;
+        Self::check_royalty(&royalty)?;
+
+        let validated_metadata = self.validate_metadata(
+            self.state.base_metadata_kind.clone(),
+            token_metadata.clone(),
+        )?;
+
+        let token_identifier = self.next_token_identifier(
+            self.state.minted_tokens_count,
+            &validated_metadata,
+            optional_token_hash,
+        )?;
+        self.check_identifier_available(&token_identifier)?;
+
+        self.write_minted_token(&token_identifier, token_owner, caller, &validated_metadata)?;
+        if let Some(royalty) = royalty {
+            self.insert_token_royalty(&token_identifier, royalty);
         }
 
-        // Depending on approve_all we either approve all or disapprove all.
-        self.set_operator_for_owner(caller, operator, approve_all);
+        // Increment the count of owned tokens.
+        let current_balance = self.get_token_balance(token_owner);
+        self.set_token_balance(token_owner, current_balance + 1);
 
-        let caller = Self::unwrap_entity(caller);
-        let operator = Self::unwrap_entity(operator);
-        match self.state.events_mode {
-            EventsMode::NoEvents => {}
-            EventsMode::CES => match approve_all {
-                true => self.emit_ces_event(ApprovalForAll::new(caller, operator)),
-                false => self.emit_ces_event(RevokedForAll::new(caller, operator)),
-            },
-            EventsMode::CEP47 => {
+        self.emit_mint_event(token_owner, token_identifier.clone(), token_metadata);
+
+        Ok(token_identifier)
+    }
+
+    // Mints the token like `mint`. If the owner is a contract, it has to acknowledge the token
+    // through its `on_cep78_received` entrypoint or the mint is reverted.
+    pub fn safe_mint(
+        &mut self,
+        token_metadata: String,
+        token_owner: Entity,
+        optional_token_hash: Option<String>,
+        data: Vec<u8>,
+    ) -> Result<TokenIdentifier, NFTCoreError> {
+        let token_identifier = self.mint(token_metadata, token_owner, optional_token_hash)?;
+        // The token is already written at this point, so a rejection has to revert the whole
+        // call instead of returning an error.
+        self.check_on_cep78_received(None, token_owner, token_identifier.clone(), data)
+            .unwrap_or_revert();
+        Ok(token_identifier)
+    }
+
+    // Mints several tokens at once. Every entry is validated before anything is written, so
+    // either the whole batch is minted or the call reverts without minting any token.
+    pub fn batch_mint(
+        &mut self,
+        tokens: Vec<MintEntry>,
+    ) -> Result<Vec<TokenIdentifier>, NFTCoreError> {
+        let caller = casper::get_caller();
+        self.check_can_mint(caller, tokens.len() as u64)?;
+
+        let first_index = self.state.minted_tokens_count;
+        let mut seen_hashes = BTreeSet::new();
+        let mut validated_tokens = Vec::with_capacity(tokens.len());
+
+        for (offset, entry) in tokens.into_iter().enumerate() {
+            let validated_metadata = self.validate_metadata(
+                self.state.base_metadata_kind.clone(),
+                entry.token_metadata.clone(),
+            )?;
+            Self::check_royalty(&entry.royalty)?;
+
+            let token_identifier = self.next_token_identifier(
+                first_index + offset as u64,
+                &validated_metadata,
+                entry.optional_token_hash.clone(),
+            )?;
+            self.check_identifier_available(&token_identifier)?;
+
+            // Hashes have to be unique within the batch as well, not only against minted tokens.
+            if let TokenIdentifier::Hash(hash) = &token_identifier {
+                if !seen_hashes.insert(hash.clone()) {
+                    return Err(NFTCoreError::DuplicateIdentifier);
+                }
+            }
+
+            validated_tokens.push((token_identifier, entry, validated_metadata));
+        }
+
+        // Aggregate the balance changes so that every owner is only written once.
+        let mut balance_increments: Vec<(Entity, u64)> = Vec::new();
+        let mut minted_identifiers = Vec::with_capacity(validated_tokens.len());
+
+        for (token_identifier, entry, validated_metadata) in validated_tokens {
+            self.write_minted_token(
+                &token_identifier,
+                entry.token_owner,
+                caller,
+                &validated_metadata,
+            )?;
+            if let Some(royalty) = entry.royalty.clone() {
+                self.insert_token_royalty(&token_identifier, royalty);
+            }
+
+            match balance_increments
+                .iter_mut()
+                .find(|(owner, _)| *owner == entry.token_owner)
+            {
+                Some((_, increment)) => *increment += 1,
+                None => balance_increments.push((entry.token_owner, 1)),
+            }
+
+            self.emit_mint_event(
+                entry.token_owner,
+                token_identifier.clone(),
+                entry.token_metadata,
+            );
+            minted_identifiers.push(token_identifier);
+        }
+
+        for (owner, increment) in balance_increments {
+            let current_balance = self.get_token_balance(owner);
+            self.set_token_balance(owner, current_balance + increment);
+        }
+
+        Ok(minted_identifiers)
+    }
+
+    // Marks token as burnt. This blocks any future call to transfer token.
+    pub fn burn(&mut self, token_identifier: TokenIdentifier) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        let caller = casper::get_caller();
+        let token_owner = self.check_burn(caller, &token_identifier)?;
+
+        self.set_token_burned(token_identifier.clone());
+        self.decrease_token_balance(token_owner, 1)?;
+
+        self.emit_burn_event(token_owner, token_identifier, caller);
+
+        Ok(())
+    }
+
+    // Burns several tokens at once. All tokens are checked before any of them is burnt, so the
+    // call either burns every token or reverts without burning any.
+    pub fn batch_burn(
+        &mut self,
+        token_identifiers: Vec<TokenIdentifier>,
+    ) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        let token_identifiers = self.resolve_token_identifiers(token_identifiers)?;
+
+        let mut token_owners = Vec::with_capacity(token_identifiers.len());
+        for (index, token_identifier) in token_identifiers.iter().enumerate() {
+            // Listing the same token twice would burn it twice.
+            if token_identifiers[..index].contains(token_identifier) {
+                return Err(NFTCoreError::DuplicateIdentifier);
+            }
+            token_owners.push(self.check_burn(caller, token_identifier)?);
+        }
+
+        // Aggregate the balance changes so that every owner is only written once.
+        let mut balance_decrements: Vec<(Entity, u64)> = Vec::new();
+        for token_owner in &token_owners {
+            match balance_decrements
+                .iter_mut()
+                .find(|(owner, _)| owner == token_owner)
+            {
+                Some((_, decrement)) => *decrement += 1,
+                None => balance_decrements.push((*token_owner, 1)),
+            }
+        }
+        for (owner, decrement) in balance_decrements {
+            self.decrease_token_balance(owner, decrement)?;
+        }
+
+        for (token_identifier, token_owner) in token_identifiers.into_iter().zip(token_owners) {
+            self.set_token_burned(token_identifier.clone());
+            self.emit_burn_event(token_owner, token_identifier, caller);
+        }
+
+        Ok(())
+    }
+
+    // Replaces the metadata of a token. Only possible when metadata is mutable, and only for the
+    // token owner or a metadata updater.
+    pub fn set_token_metadata(
+        &mut self,
+        token_identifier: TokenIdentifier,
+        updated_token_metadata: String,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        if let MetadataMutability::Immutable = self.state.metadata_mutability {
+            return Err(NFTCoreError::ForbiddenMetadataUpdate);
+        }
+
+        let caller = casper::get_caller();
+
+        let Some(token_owner) = self.read_token_owner(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        if token_owner != caller && !self.holds_role(caller, Role::MetadataUpdater) {
+            return Err(NFTCoreError::InvalidTokenOwner);
+        }
+
+        if self.read_token_burned(&token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
+
+        let validated_metadata = self.validate_metadata(
+            self.state.base_metadata_kind.clone(),
+            updated_token_metadata.clone(),
+        )?;
+        self.insert_metadata(&token_identifier, &validated_metadata);
+
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => self.emit_ces_event(MetadataUpdated::new(
+                token_identifier,
+                updated_token_metadata,
+            )),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::MetadataUpdate {
+                token_id: token_identifier,
+            }),
+        }
+
+        Ok(())
+    }
+
+    // Locks a token so that it can't be transferred, approved or burnt until it's unlocked.
+    pub fn lock_token(&mut self, token_identifier: TokenIdentifier) -> Result<(), NFTCoreError> {
+        self.set_token_locked(token_identifier, true)
+    }
+
+    pub fn unlock_token(&mut self, token_identifier: TokenIdentifier) -> Result<(), NFTCoreError> {
+        self.set_token_locked(token_identifier, false)
+    }
+
+    pub fn is_locked(&self, token_identifier: TokenIdentifier) -> Result<bool, NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        if self.read_token_owner(&token_identifier).is_none() {
+            return Err(NFTCoreError::MissingTokenOwner);
+        }
+        Ok(self.read_token_locked(&token_identifier))
+    }
+
+    // Lets `user` use the token until `expires_at` (block time, in milliseconds) without owning
+    // it. Passing `None` clears the current user. The user is cleared on every transfer.
+    pub fn set_user(
+        &mut self,
+        token_identifier: TokenIdentifier,
+        user: Option<Entity>,
+        expires_at: u64,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        self.check_not_paused()?;
+
+        let caller = casper::get_caller();
+
+        let Some(owner) = self.read_token_owner(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        // Only the token owner, one of its approved accounts or an operator can set the user.
+        let is_owner = caller == owner;
+        let is_approved = !is_owner && self.is_approved_spender(&token_identifier, caller);
+        let is_operator = !is_owner && !is_approved && self.read_operator(owner, caller);
+
+        if !is_owner && !is_approved && !is_operator {
+            return Err(NFTCoreError::InvalidTokenOwner);
+        }
+
+        if self.read_token_burned(&token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
+
+        let token_user = user.map(|user| TokenUser { user, expires_at });
+        let mut data = self
+            .state
+            .store
+            .data
+            .get(&token_identifier)
+            .unwrap_or_default();
+        data.user = token_user;
+        self.state.store.data.insert(&token_identifier, &data);
+
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => {
+                self.emit_ces_event(UpdateUser::new(token_identifier, user, expires_at))
+            }
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::UpdateUser {
+                token_id: token_identifier,
+                user,
+                expires_at,
+            }),
+        }
+
+        Ok(())
+    }
+
+    // Returns the current user of the token, or `None` if there is none or it has expired.
+    pub fn user_of(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Option<Entity>, NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        let Some(data) = self.state.store.data.get(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        match data.user {
+            Some(token_user) if token_user.expires_at > casper::get_block_time() => {
+                Ok(Some(token_user.user))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // Approves `spender` for the token. With `expires_at` (block time, in milliseconds) the
+    // approval lapses on its own once that time is reached.
+    pub fn approve(
+        &mut self,
+        operator: Option<Entity>,
+        spender: Entity,
+        token_identifier: TokenIdentifier,
+        expires_at: Option<u64>,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        self.check_not_paused()?;
+
+        // If we are in minter, assigned or soulbound mode it makes no sense to approve an account.
+        // Hence we revert.
+        if let OwnershipMode::Minter | OwnershipMode::Assigned | OwnershipMode::Soulbound =
+            self.state.ownership_mode
+        {
+            return Err(NFTCoreError::InvalidOwnershipMode);
+        }
+
+        let caller = casper::get_caller();
+
+        let Some(owner) = self.read_token_owner(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        // Revert if caller is not token owner nor operator.
+        // Only the token owner or an operator can approve an account
+        let is_owner = caller == owner;
+        let is_operator = !is_owner && self.read_operator(owner, caller);
+
+        if !is_owner && !is_operator {
+            return Err(NFTCoreError::InvalidTokenOwner);
+        }
+
+        // We assume a burnt token cannot be approved
+        if self.read_token_burned(&token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
+
+        if self.read_token_locked(&token_identifier) {
+            return Err(NFTCoreError::TokenIsLocked);
+        }
+
+        let spender = match operator {
+            None => spender,
+            // Deprecated in favor of spender
+            Some(operator) => operator,
+        };
+
+        // If token owner or operator tries to approve itself that's probably a mistake and we revert.
+        if caller == spender {
+            return Err(NFTCoreError::InvalidAccount);
+        }
+
+        // An approval which has already expired on arrival is a mistake as well.
+        if Self::is_expired(expires_at) {
+            return Err(NFTCoreError::InvalidExpiration);
+        }
+
+        self.set_approved(&token_identifier, spender, expires_at)?;
+
+        self.emit_approval_event(owner, spender, token_identifier);
+
+        Ok(())
+    }
+
+    // Approves `spender` on behalf of `owner`, who authorized it by signing a `PermitMessage`
+    // off-chain. `public_key` is the owner's ed25519 key, it has to hash to the owner's account.
+    // Every permit consumes the owner's current nonce, so it can only be used once.
+    pub fn permit(
+        &mut self,
+        owner: Entity,
+        spender: Entity,
+        token_identifier: TokenIdentifier,
+        deadline: u64,
+        nonce: u64,
+        public_key: Vec<u8>,
+        signature: Vec<u8>,
+    ) -> Result<(), NFTCoreError> {
+        // The owner signed the identifier in the form they passed it, which may be an ordinal
+        // even though the collection stores the token under its hash.
+        let signed_identifier = token_identifier.clone();
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        self.check_not_paused()?;
+
+        // If we are in minter, assigned or soulbound mode it makes no sense to approve an account.
+        // Hence we revert.
+        if let OwnershipMode::Minter | OwnershipMode::Assigned | OwnershipMode::Soulbound =
+            self.state.ownership_mode
+        {
+            return Err(NFTCoreError::InvalidOwnershipMode);
+        }
+
+        if casper::get_block_time() > deadline {
+            return Err(NFTCoreError::PermitExpired);
+        }
+
+        let Some(token_owner) = self.read_token_owner(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        if token_owner != owner {
+            return Err(NFTCoreError::InvalidTokenOwner);
+        }
+
+        if self.read_token_burned(&token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
+
+        if self.read_token_locked(&token_identifier) {
+            return Err(NFTCoreError::TokenIsLocked);
+        }
+
+        if owner == spender {
+            return Err(NFTCoreError::InvalidAccount);
+        }
+
+        let mut owner_data = self.state.store.entity_data.get(&owner).unwrap_or_default();
+        if owner_data.permit_nonce != nonce {
+            return Err(NFTCoreError::InvalidPermitNonce);
+        }
+
+        let message = PermitMessage {
+            domain: PERMIT_DOMAIN.into(),
+            contract: casper::get_callee(),
+            package_hash: self.state.package_hash.clone(),
+            chain_name: self.state.chain_name.clone(),
+            owner,
+            spender,
+            token_identifier: signed_identifier,
+            deadline,
+            nonce,
+        };
+        Self::verify_permit_signature(owner, &public_key, &signature, &message)?;
+
+        // Only consume the nonce once the approval is actually recorded.
+        self.set_approved(&token_identifier, spender, None)?;
+
+        owner_data.permit_nonce += 1;
+        self.state.store.entity_data.insert(&owner, &owner_data);
+
+        self.emit_approval_event(owner, spender, token_identifier);
+
+        Ok(())
+    }
+
+    // The nonce the next permit signed by `owner` has to use.
+    pub fn permit_nonce(&self, owner: Entity) -> Result<u64, NFTCoreError> {
+        match self.state.store.entity_data.get(&owner) {
+            Some(data) => Ok(data.permit_nonce),
+            None => Ok(0),
+        }
+    }
+
+    // Revokes an account as approved for an identified token transfer. Without a `spender` every
+    // approval of the token is revoked.
+    pub fn revoke(
+        &mut self,
+        token_identifier: TokenIdentifier,
+        spender: Option<Entity>,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        self.check_not_paused()?;
+
+        // If we are in minter, assigned or soulbound mode it makes no sense to approve an account.
+        // Hence we revert.
+        if let OwnershipMode::Minter | OwnershipMode::Assigned | OwnershipMode::Soulbound =
+            self.state.ownership_mode
+        {
+            return Err(NFTCoreError::InvalidOwnershipMode);
+        }
+
+        let caller = casper::get_caller();
+
+        let Some(owner) = self.read_token_owner(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        // Revert if caller is not token owner nor operator.
+        // Only the token owner or an operator can approve an account
+        let is_owner = caller == owner;
+        let is_operator = !is_owner && self.read_operator(owner, caller);
+
+        if !is_owner && !is_operator {
+            return Err(NFTCoreError::InvalidTokenOwner);
+        }
+
+        // We assume a burnt token cannot be approved
+        if self.read_token_burned(&token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
+
+        match spender {
+            Some(spender) => self.remove_approved(&token_identifier, spender)?,
+            None => self.clear_approved(&token_identifier)?,
+        };
+
+        let owner = Self::unwrap_entity(owner);
+        let spender = spender.map(Self::unwrap_entity);
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => {
+                self.emit_ces_event(ApprovalRevoked::new(owner, spender, token_identifier))
+            }
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::ApprovalRevoked {
+                owner,
+                spender,
+                token_id: token_identifier,
+            }),
+        };
+
+        Ok(())
+    }
+
+    // Returns every account currently approved for the token. Expired approvals are left out.
+    pub fn approved_spenders(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<Vec<Entity>, NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        let Some(data) = self.state.store.data.get(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        Ok(data
+            .approved
+            .iter()
+            .filter(|approved| !Self::is_expired(approved.expires_at))
+            .map(|approved| approved.spender)
+            .collect())
+    }
+
+    // Approves the specified operator for transfer of owner's tokens. With `expires_at` (block
+    // time, in milliseconds) the grant lapses on its own once that time is reached.
+    pub fn set_approval_for_all(
+        &mut self,
+        approve_all: bool,
+        operator: Entity,
+        expires_at: Option<u64>,
+    ) -> Result<(), NFTCoreError> {
+        self.check_not_paused()?;
+
+        // If we are in minter, assigned or soulbound mode it makes no sense to approve an operator.
+        // Hence we revert.
+        if let OwnershipMode::Minter | OwnershipMode::Assigned | OwnershipMode::Soulbound =
+            self.state.ownership_mode
+        {
+            return Err(NFTCoreError::InvalidOwnershipMode);
+        }
+
+        let caller = casper::get_caller();
+
+        // If caller tries to approve itself as operator that's probably a mistake and we revert.
+        if caller == operator {
+            return Err(NFTCoreError::InvalidAccount);
+        }
+
+        // An approval which has already expired on arrival is a mistake as well.
+        if approve_all && Self::is_expired(expires_at) {
+            return Err(NFTCoreError::InvalidExpiration);
+        }
+
+        // Depending on approve_all we either approve all or disapprove all.
+        self.set_operator_for_owner(caller, operator, approve_all, expires_at);
+
+        let caller = Self::unwrap_entity(caller);
+        let operator = Self::unwrap_entity(operator);
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => match approve_all {
+                true => self.emit_ces_event(ApprovalForAll::new(caller, operator)),
+                false => self.emit_ces_event(RevokedForAll::new(caller, operator)),
+            },
+            EventsMode::CEP47 => {
                 self.write_cep47_event(match approve_all {
                     true => CEP47Event::ApprovalForAll {
                         owner: caller,
@@ -513,38 +1027,568 @@ impl NFTContract {
             }
         }
 
-        Ok(())
-    }
+        Ok(())
+    }
+
+    pub fn is_approved_for_all(
+        &self,
+        owner: Entity,
+        operator: Entity,
+    ) -> Result<bool, NFTCoreError> {
+        let is_operator = self.read_operator(owner, operator);
+        Ok(is_operator)
+    }
+
+    // Transfers token from token owner to specified account. Transfer will go through if caller is
+    // owner or an approved account or an operator. Transfer will fail if OwnershipMode is Minter or
+    // Assigned.
+    pub fn transfer(
+        &mut self,
+        source_owner: Entity,
+        target_owner: Entity,
+        token_identifier: TokenIdentifier,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        let caller = casper::get_caller();
+        self.check_transfer(caller, source_owner, target_owner, &token_identifier)?;
+
+        // TODO: Add hash migration with ORLM
+
+        self.insert_token_owner(&token_identifier, target_owner);
+        self.clear_approved(&token_identifier).ok();
+        self.clear_user(&token_identifier);
+
+        // Update the from_account balance
+        self.decrease_token_balance(source_owner, 1)?;
+
+        // Update the to_account balance
+        let updated_to_account_balance = self.get_token_balance(target_owner) + 1u64;
+        self.set_token_balance(target_owner, updated_to_account_balance);
+
+        self.emit_transfer_event(caller, source_owner, target_owner, token_identifier);
+
+        Ok(())
+    }
+
+    // Transfers several tokens owned by `source_owner` to `target_owner`. The same authorization
+    // rules as for `transfer` apply to every token, and the call either moves all of them or
+    // reverts without moving any.
+    pub fn batch_transfer(
+        &mut self,
+        source_owner: Entity,
+        target_owner: Entity,
+        token_identifiers: Vec<TokenIdentifier>,
+    ) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        let token_identifiers = self.resolve_token_identifiers(token_identifiers)?;
+
+        for (index, token_identifier) in token_identifiers.iter().enumerate() {
+            // A token listed twice would be counted twice against the balances.
+            if token_identifiers[..index].contains(token_identifier) {
+                return Err(NFTCoreError::DuplicateIdentifier);
+            }
+            self.check_transfer(caller, source_owner, target_owner, token_identifier)?;
+        }
+
+        let transferred_count = token_identifiers.len() as u64;
+        self.decrease_token_balance(source_owner, transferred_count)?;
+        let updated_to_account_balance = self.get_token_balance(target_owner) + transferred_count;
+        self.set_token_balance(target_owner, updated_to_account_balance);
+
+        for token_identifier in token_identifiers {
+            self.insert_token_owner(&token_identifier, target_owner);
+            self.clear_approved(&token_identifier).ok();
+            self.clear_user(&token_identifier);
+            self.emit_transfer_event(caller, source_owner, target_owner, token_identifier);
+        }
+
+        Ok(())
+    }
+
+    // Transfers the token like `transfer`. If the recipient is a contract, it has to acknowledge
+    // the token through its `on_cep78_received` entrypoint or the transfer is reverted.
+    pub fn safe_transfer(
+        &mut self,
+        source_owner: Entity,
+        target_owner: Entity,
+        token_identifier: TokenIdentifier,
+        data: Vec<u8>,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        self.transfer(source_owner, target_owner, token_identifier.clone())?;
+        // The token already moved, so a rejection has to revert the whole call.
+        self.check_on_cep78_received(Some(source_owner), target_owner, token_identifier, data)
+            .unwrap_or_revert();
+        Ok(())
+    }
+
+    // Moves a soulbound token to a replacement address, e.g. after its holder lost their key.
+    // Only the issuer of the token can do this.
+    pub fn recover_token(
+        &mut self,
+        token_identifier: TokenIdentifier,
+        new_owner: Entity,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        self.check_not_paused()?;
+
+        if OwnershipMode::Soulbound != self.state.ownership_mode {
+            return Err(NFTCoreError::InvalidOwnershipMode);
+        }
+
+        let caller = casper::get_caller();
+
+        let Some(data) = self.state.store.data.get(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+        let Some(previous_owner) = data.owner else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        if data.issuer != Some(caller) {
+            return Err(NFTCoreError::InvalidTokenMinter);
+        }
+
+        if self.read_token_burned(&token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
+
+        if self.read_token_locked(&token_identifier) {
+            return Err(NFTCoreError::TokenIsLocked);
+        }
+
+        self.insert_token_owner(&token_identifier, new_owner);
+        self.clear_user(&token_identifier);
+        self.decrease_token_balance(previous_owner, 1)?;
+        let updated_new_owner_balance = self.get_token_balance(new_owner) + 1u64;
+        self.set_token_balance(new_owner, updated_new_owner_balance);
+
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => self.emit_ces_event(TokenRecovered::new(
+                token_identifier,
+                previous_owner,
+                new_owner,
+                caller,
+            )),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::TokenRecovered {
+                token_id: token_identifier,
+                previous_owner,
+                new_owner,
+                issuer: caller,
+            }),
+        }
+
+        Ok(())
+    }
+
+    // Returns who should receive royalties for a sale of the token at `sale_price` and how much.
+    // The token's own royalty takes precedence over the collection default.
+    pub fn royalty_info(
+        &self,
+        token_identifier: TokenIdentifier,
+        sale_price: u64,
+    ) -> Result<Option<RoyaltyPayment>, NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        let Some(data) = self.state.store.data.get(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        let Some(royalty) = data.royalty.or(self.state.default_royalty.clone()) else {
+            return Ok(None);
+        };
+
+        let amount =
+            sale_price as u128 * royalty.basis_points as u128 / MAX_ROYALTY_BASIS_POINTS as u128;
+
+        Ok(Some(RoyaltyPayment {
+            receiver: royalty.receiver,
+            amount: amount as u64,
+        }))
+    }
+
+    pub fn balance_of(&self, owner: Entity) -> Result<u64, NFTCoreError> {
+        let balance = self.get_token_balance(owner);
+        Ok(balance)
+    }
+
+    pub fn owner_of(&self, identifier: TokenIdentifier) -> Result<Entity, NFTCoreError> {
+        let identifier = self.resolve_token_identifier(identifier)?;
+
+        let Some(owner) = self.read_token_owner(&identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        Ok(owner)
+    }
+
+    // Returns the hash of the token minted as `index`-th token, in hash identifier mode.
+    pub fn token_hash_by_index(&self, index: u64) -> Result<String, NFTCoreError> {
+        if NFTIdentifierMode::Hash != self.state.identifier_mode {
+            return Err(NFTCoreError::InvalidIdentifierMode);
+        }
+
+        self.state
+            .store
+            .hash_by_index
+            .get(&index)
+            .ok_or(NFTCoreError::MissingHashByIndex)
+    }
+
+    // Returns the position in minting order of the token with the given hash, in hash identifier
+    // mode.
+    pub fn token_index_by_hash(&self, hash: String) -> Result<u64, NFTCoreError> {
+        if NFTIdentifierMode::Hash != self.state.identifier_mode {
+            return Err(NFTCoreError::InvalidIdentifierMode);
+        }
+
+        self.state
+            .store
+            .index_by_hash
+            .get(&hash)
+            .ok_or(NFTCoreError::MissingIndexByHash)
+    }
+
+    pub fn collection_info(&self) -> Result<CollectionInfo, NFTCoreError> {
+        Ok(CollectionInfo {
+            collection_name: self.state.collection_name.clone(),
+            collection_symbol: self.state.collection_symbol.clone(),
+            total_token_supply: self.state.total_token_supply,
+            minted_tokens_count: self.state.minted_tokens_count,
+            circulating_supply: self.get_circulating_supply(),
+            allow_minting: self.state.allow_minting,
+            minting_mode: self.state.minting_mode.clone(),
+            ownership_mode: self.state.ownership_mode.clone(),
+            nft_kind: self.state.nft_kind.clone(),
+            base_metadata_kind: self.state.base_metadata_kind.clone(),
+            metadata_mutability: self.state.metadata_mutability.clone(),
+            burn_mode: self.state.burn_mode.clone(),
+            identifier_mode: self.state.identifier_mode.clone(),
+            events_mode: self.state.events_mode.clone(),
+        })
+    }
+
+    pub fn collection_name(&self) -> Result<String, NFTCoreError> {
+        Ok(self.state.collection_name.clone())
+    }
+
+    pub fn collection_symbol(&self) -> Result<String, NFTCoreError> {
+        Ok(self.state.collection_symbol.clone())
+    }
+
+    // `None` when the collection has no supply cap.
+    pub fn total_supply(&self) -> Result<Option<u64>, NFTCoreError> {
+        Ok(self.state.total_token_supply)
+    }
+
+    pub fn number_of_minted_tokens(&self) -> Result<u64, NFTCoreError> {
+        Ok(self.state.minted_tokens_count)
+    }
+
+    // Number of tokens currently in existence, i.e. minted tokens that have not been burnt.
+    pub fn circulating_supply(&self) -> Result<u64, NFTCoreError> {
+        Ok(self.get_circulating_supply())
+    }
+
+    // Validates a token identifier passed to an entrypoint and returns the identifier the token
+    // is stored under. Every entrypoint goes through here, so that malformed identifiers are
+    // always reported as `InvalidTokenIdentifier` while `MissingTokenOwner` is left for tokens
+    // that simply don't exist. In hash mode, ordinals are accepted as well and resolved to the
+    // hash of the token minted at that index.
+    fn resolve_token_identifier(
+        &self,
+        token_identifier: TokenIdentifier,
+    ) -> Result<TokenIdentifier, NFTCoreError> {
+        match (&self.state.identifier_mode, token_identifier) {
+            (NFTIdentifierMode::Ordinal, TokenIdentifier::Ordinal(index)) => {
+                // Revert if token_id is out of bounds
+                if index >= self.state.minted_tokens_count {
+                    return Err(NFTCoreError::InvalidTokenIdentifier);
+                }
+                Ok(TokenIdentifier::Ordinal(index))
+            }
+            (NFTIdentifierMode::Ordinal, TokenIdentifier::Hash(_)) => {
+                Err(NFTCoreError::InvalidTokenIdentifier)
+            }
+            (NFTIdentifierMode::Hash, TokenIdentifier::Ordinal(index)) => self
+                .state
+                .store
+                .hash_by_index
+                .get(&index)
+                .map(TokenIdentifier::Hash)
+                .ok_or(NFTCoreError::InvalidTokenIdentifier),
+            (NFTIdentifierMode::Hash, TokenIdentifier::Hash(hash)) => {
+                Self::check_token_hash(&hash).map_err(|_| NFTCoreError::InvalidTokenIdentifier)?;
+                Ok(TokenIdentifier::Hash(hash))
+            }
+        }
+    }
+
+    fn resolve_token_identifiers(
+        &self,
+        token_identifiers: Vec<TokenIdentifier>,
+    ) -> Result<Vec<TokenIdentifier>, NFTCoreError> {
+        token_identifiers
+            .into_iter()
+            .map(|token_identifier| self.resolve_token_identifier(token_identifier))
+            .collect()
+    }
+
+    // The supply can only ever shrink, and never below the tokens that already exist. Collections
+    // without a cap can still be given one.
+    fn update_total_token_supply(&mut self, total_token_supply: u64) -> Result<(), NFTCoreError> {
+        if total_token_supply == 0 {
+            return Err(NFTCoreError::CannotUpgradeWithZeroSupply);
+        }
+
+        if self
+            .state
+            .total_token_supply
+            .is_some_and(|current_supply| total_token_supply > current_supply)
+        {
+            return Err(NFTCoreError::CannotUpgradeToMoreSupply);
+        }
+
+        if total_token_supply < self.state.minted_tokens_count {
+            return Err(NFTCoreError::InvalidTotalTokenSupply);
+        }
+
+        self.state.total_token_supply = Some(total_token_supply);
+
+        Ok(())
+    }
+
+    fn check_can_migrate(&self) -> Result<(), NFTCoreError> {
+        if !self.is_installer(casper::get_caller()) {
+            return Err(NFTCoreError::InvalidAccount);
+        }
+
+        if !self.state.allow_migration {
+            return Err(NFTCoreError::MissingMigrationFlag);
+        }
+
+        if self.state.migration_finalized {
+            return Err(NFTCoreError::ContractAlreadyMigrated);
+        }
+
+        Ok(())
+    }
+
+    // Number of tokens that can still be minted, `None` when the supply is unlimited.
+    fn remaining_supply(&self) -> Option<u64> {
+        self.state.total_token_supply.map(|total_token_supply| {
+            total_token_supply.saturating_sub(self.state.minted_tokens_count)
+        })
+    }
+
+    // Checks whether the caller may mint `count` more tokens right now.
+    fn check_can_mint(&self, caller: Entity, count: u64) -> Result<(), NFTCoreError> {
+        // The contract owner can toggle the minting behavior on and off over time.
+        // The contract is toggled on by default.
+        // If contract minting behavior is currently toggled off we revert.
+        if !self.state.allow_minting {
+            return Err(NFTCoreError::MintingIsPaused);
+        }
+
+        // Migrated tokens keep their original identifiers, so nothing can be minted in between.
+        if self.state.allow_migration && !self.state.migration_finalized {
+            return Err(NFTCoreError::MintingIsPaused);
+        }
+
+        // Revert if the token supply would be exhausted.
+        if self
+            .remaining_supply()
+            .is_some_and(|remaining_supply| count > remaining_supply)
+        {
+            return Err(NFTCoreError::TokenSupplyDepleted);
+        }
+
+        // Revert if minting is private and caller is neither installer nor minter.
+        if MintingMode::Installer == self.state.minting_mode {
+            if !self.holds_role(caller, Role::Minter) {
+                return Err(NFTCoreError::InvalidMinter);
+            }
+        }
+
+        // Revert if minting is acl and caller is not whitelisted.
+        if MintingMode::Acl == self.state.minting_mode {
+            if !self.is_whitelisted(caller) {
+                return Err(NFTCoreError::InvalidMinter);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Derives the identifier of the token about to be minted. In hash mode `token_metadata` has
+    // to be the validated metadata, so that formatting differences don't change the hash.
+    fn next_token_identifier(
+        &self,
+        index: u64,
+        token_metadata: &str,
+        optional_token_hash: Option<String>,
+    ) -> Result<TokenIdentifier, NFTCoreError> {
+        match self.state.identifier_mode {
+            NFTIdentifierMode::Ordinal => Ok(TokenIdentifier::Ordinal(index)),
+            NFTIdentifierMode::Hash => match optional_token_hash {
+                Some(hash) => {
+                    Self::check_token_hash(&hash)?;
+                    Ok(TokenIdentifier::Hash(hash))
+                }
+                None => Ok(TokenIdentifier::Hash(
+                    self.generate_hash(token_metadata.trim().to_string()),
+                )),
+            },
+        }
+    }
+
+    // Supplied hashes have to look exactly like the ones we generate ourselves.
+    fn check_token_hash(hash: &str) -> Result<(), NFTCoreError> {
+        let is_lowercase_hex = hash
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+
+        if hash.len() != TOKEN_HASH_HEX_LENGTH || !is_lowercase_hex {
+            return Err(NFTCoreError::InvalidIdentifier);
+        }
+
+        Ok(())
+    }
+
+    fn check_identifier_available(
+        &self,
+        token_identifier: &TokenIdentifier,
+    ) -> Result<(), NFTCoreError> {
+        if let TokenIdentifier::Hash(hash) = token_identifier {
+            if self.state.store.index_by_hash.get(hash).is_some() {
+                return Err(NFTCoreError::DuplicateIdentifier);
+            }
+        }
+        Ok(())
+    }
+
+    // Writes an already validated token to the store. Balances are left to the caller so that
+    // batch operations can update them once per owner.
+    fn write_minted_token(
+        &mut self,
+        token_identifier: &TokenIdentifier,
+        token_owner: Entity,
+        token_issuer: Entity,
+        token_metadata: &str,
+    ) -> Result<(), NFTCoreError> {
+        // The contract's ownership behavior (determined at installation) determines,
+        // who owns the NFT we are about to mint.
+        self.insert_token_owner(token_identifier, token_owner);
+        self.insert_token_issuer(token_identifier, token_issuer);
+        self.insert_metadata(token_identifier, token_metadata);
+
+        // Update the forward and reverse trackers
+        if NFTIdentifierMode::Hash == self.state.identifier_mode {
+            self.insert_hash_id_lookups(token_identifier)?;
+        }
+
+        // Increment number_of_minted_tokens by one
+        self.state.minted_tokens_count += 1;
+
+        Ok(())
+    }
+
+    fn emit_mint_event(
+        &mut self,
+        token_owner: Entity,
+        token_identifier: TokenIdentifier,
+        token_metadata: String,
+    ) {
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => self.emit_ces_event(Mint::new(
+                Self::unwrap_entity(token_owner),
+                token_identifier,
+                token_metadata,
+            )),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::Mint {
+                recipient: Self::unwrap_entity(token_owner),
+                token_id: token_identifier,
+            }),
+        }
+    }
+
+    // Checks whether the caller may burn the token and returns its current owner.
+    fn check_burn(
+        &self,
+        caller: Entity,
+        token_identifier: &TokenIdentifier,
+    ) -> Result<Entity, NFTCoreError> {
+        self.check_not_paused()?;
+
+        if let BurnMode::NonBurnable = self.state.burn_mode {
+            return Err(NFTCoreError::InvalidBurnMode);
+        }
+
+        let Some(token_owner) = self.read_token_owner(token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        // Check if caller is owner
+        let is_owner = token_owner == caller;
+
+        // Check if caller is operator to execute burn
+        // With operator package mode check if caller's package is operator to let contract execute burn
+        let is_operator = if !is_owner {
+            self.read_operator(token_owner, caller)
+        } else {
+            false
+        };
+
+        // Burners can burn any token as a last resort.
+        let is_burner = !is_owner && !is_operator && self.holds_role(caller, Role::Burner);
+
+        // Revert if caller is not token_owner nor operator for the owner nor a burner
+        if !is_owner && !is_operator && !is_burner {
+            return Err(NFTCoreError::InvalidTokenOwner);
+        }
+
+        // It makes sense to keep this token as owned by the caller. It just happens that the caller
+        // owns a burnt token. That's all. Similarly, we should probably also not change the
+        // owned_tokens dictionary.
+        if self.read_token_burned(token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
 
-    pub fn is_approved_for_all(
-        &self,
-        owner: Entity,
-        operator: Entity,
-    ) -> Result<bool, NFTCoreError> {
-        let is_operator = self.caller_is_operator_for_owner(owner, operator);
-        Ok(is_operator)
+        if self.read_token_locked(token_identifier) {
+            return Err(NFTCoreError::TokenIsLocked);
+        }
+
+        Ok(token_owner)
     }
 
-    // Transfers token from token owner to specified account. Transfer will go through if caller is
-    // owner or an approved account or an operator. Transfer will fail if OwnershipMode is Minter or
-    // Assigned.
-    pub fn transfer(
-        &mut self,
+    // Checks whether the caller may move the token out of `source_owner`'s possession.
+    fn check_transfer(
+        &self,
+        caller: Entity,
         source_owner: Entity,
         target_owner: Entity,
-        token_identifier: TokenIdentifier,
+        token_identifier: &TokenIdentifier,
     ) -> Result<(), NFTCoreError> {
-        // If we are in minter or assigned mode we are not allowed to transfer ownership of token, hence
-        // we revert.
-        if let OwnershipMode::Minter | OwnershipMode::Assigned = self.state.ownership_mode {
+        self.check_not_paused()?;
+
+        // If we are in minter, assigned or soulbound mode we are not allowed to transfer ownership of
+        // token, hence we revert.
+        if let OwnershipMode::Minter | OwnershipMode::Assigned | OwnershipMode::Soulbound =
+            self.state.ownership_mode
+        {
             return Err(NFTCoreError::InvalidOwnershipMode);
         }
 
-        if self.read_token_burned(&token_identifier) {
+        if self.read_token_burned(token_identifier) {
             return Err(NFTCoreError::PreviouslyBurntToken);
         }
 
-        let Some(owner) = self.read_token_owner(&token_identifier) else {
+        if self.read_token_locked(token_identifier) {
+            return Err(NFTCoreError::TokenIsLocked);
+        }
+
+        let Some(owner) = self.read_token_owner(token_identifier) else {
             return Err(NFTCoreError::MissingTokenOwner);
         };
 
@@ -552,17 +1596,11 @@ impl NFTContract {
             return Err(NFTCoreError::InvalidAccount);
         }
 
-        let caller = casper::get_caller();
-
         // Check if caller is owner
         let is_owner = owner == caller;
 
         // Check if caller is approved to execute transfer
-        let is_approved = !is_owner
-            && match self.get_approved(&token_identifier) {
-                Ok(Some(maybe_approved)) => caller == maybe_approved,
-                Ok(None) | Err(_) => false,
-            };
+        let is_approved = !is_owner && self.is_approved_spender(token_identifier, caller);
 
         // Check if caller is operator to execute transfer
         let is_operator = if !is_owner && !is_approved {
@@ -576,35 +1614,190 @@ impl NFTContract {
             return Err(NFTCoreError::InvalidTokenOwner);
         }
 
-        // TODO: Add hash migration with ORLM
+        // Let the transfer filter contract, if any, have the final word.
+        if let Some(Entity::Contract(address)) = self.state.transfer_filter_contract {
+            let filter_result = ContractHandle::<CEP78TransferFilterRef>::from_address(address)
+                .call(|filter| {
+                    filter.can_transfer(source_owner, target_owner, token_identifier.clone())
+                })
+                .map_err(|_| NFTCoreError::InvalidTransferFilterContract)?;
 
-        if self.read_token_owner(&token_identifier) != Some(source_owner) {
-            return Err(NFTCoreError::InvalidTokenOwner);
+            if let TransferFilterContractResult::DenyTransfer = filter_result {
+                return Err(NFTCoreError::TransferFilterContractDenied);
+            }
         }
-        self.insert_token_owner(&token_identifier, target_owner);
 
-        // Update the from_account balance
-        let balance = self.get_token_balance(source_owner);
-        match balance {
-            0 => return Err(NFTCoreError::FatalTokenIdDuplication),
-            _ => {
-                self.set_token_balance(
-                    source_owner,
-                    if balance > 0u64 {
-                        balance - 1u64
-                    } else {
-                        // This should never happen...
-                        return Err(NFTCoreError::FatalTokenIdDuplication);
-                    },
-                );
+        Ok(())
+    }
+
+    fn state_from_config(config: InstallConfig, installer: Entity) -> CEP78State {
+        let mut store = StateStore::default();
+        store.json_schema = config.json_schema;
+
+        // Minting in ACL mode looks the caller up in the entity data, not in the list itself.
+        for entity in &config.acl_whitelist {
+            let mut data = store.entity_data.get(entity).unwrap_or_default();
+            data.whitelisted = true;
+            store.entity_data.insert(entity, &data);
+        }
+
+        CEP78State {
+            version: STATE_VERSION,
+            collection_name: config.collection_name,
+            collection_symbol: config.collection_symbol,
+            total_token_supply: config.total_token_supply,
+            allow_minting: config.allow_minting,
+            minting_mode: config.minting_mode,
+            ownership_mode: config.ownership_mode,
+            nft_kind: config.nft_kind,
+            whitelist_mode: config.whitelist_mode,
+            acl_whitelist: config.acl_whitelist,
+            acl_package_mode: config.acl_package_mode,
+            package_operator_mode: config.package_operator_mode,
+            package_hash: config.package_hash,
+            base_metadata_kind: config.base_metadata_kind,
+            optional_metadata: config.optional_metadata,
+            additional_required_metadata: config.additional_required_metadata,
+            identifier_mode: config.identifier_mode,
+            metadata_mutability: config.metadata_mutability,
+            burn_mode: config.burn_mode,
+            operator_burn_mode: config.operator_burn_mode,
+            transfer_filter_contract: config.transfer_filter_contract,
+            token_lock_mode: config.token_lock_mode,
+            default_royalty: config.default_royalty,
+            chain_name: config.chain_name,
+            hash_algorithm: config.hash_algorithm,
+            allow_migration: config.allow_migration,
+            migration_finalized: false,
+            minted_tokens_count: 0,
+            events_mode: config.events_mode,
+            installer: Some(installer),
+            pending_installer: None,
+            paused: false,
+            store,
+        }
+    }
+
+    // Validates the configuration passed to the constructor.
+    pub(crate) fn check_install_configuration(config: &InstallConfig) -> Result<(), NFTCoreError> {
+        if config.collection_name.is_empty() {
+            return Err(NFTCoreError::InvalidCollectionName);
+        }
+
+        if config.collection_symbol.is_empty() {
+            return Err(NFTCoreError::InvalidCollectionSymbol);
+        }
+
+        if config.total_token_supply == Some(0) {
+            return Err(NFTCoreError::CannotInstallWithZeroSupply);
+        }
+
+        // An ACL without any whitelisted entity would make minting impossible.
+        if MintingMode::Acl == config.minting_mode && config.acl_whitelist.is_empty() {
+            return Err(NFTCoreError::EmptyACLWhitelist);
+        }
+
+        // Hash identifiers are derived from the metadata, so the metadata can't be mutable.
+        if NFTIdentifierMode::Hash == config.identifier_mode
+            && MetadataMutability::Mutable == config.metadata_mutability
+        {
+            return Err(NFTCoreError::InvalidMetadataMutability);
+        }
+
+        let optional_metadata = Self::parse_metadata_kinds(&config.optional_metadata)
+            .ok_or(NFTCoreError::InvalidOptionalMetadata)?;
+        let additional_required_metadata =
+            Self::parse_metadata_kinds(&config.additional_required_metadata)
+                .ok_or(NFTCoreError::InvalidAdditionalRequiredMetadata)?;
+
+        let uses_custom_metadata = NFTMetadataKind::CustomValidated == config.base_metadata_kind
+            || optional_metadata.contains(&NFTMetadataKind::CustomValidated)
+            || additional_required_metadata.contains(&NFTMetadataKind::CustomValidated);
+
+        if uses_custom_metadata {
+            let Some(json_schema) = &config.json_schema else {
+                return Err(NFTCoreError::MissingJsonSchema);
+            };
+            serde_json_wasm::from_str::<CustomMetadataSchema>(json_schema)
+                .map_err(|_| NFTCoreError::InvalidJsonSchema)?;
+        }
+
+        Self::check_royalty(&config.default_royalty)?;
+
+        // A transfer filter only makes sense for transferable tokens and has to be a contract.
+        match config.transfer_filter_contract {
+            None => {}
+            Some(_) if OwnershipMode::Transferable != config.ownership_mode => {
+                return Err(NFTCoreError::TransferFilterContractNeedsTransferableMode)
             }
+            Some(Entity::Account(_)) => return Err(NFTCoreError::InvalidTransferFilterContract),
+            Some(Entity::Contract(_)) => {}
         }
 
-        // Update the to_account balance
-        let updated_to_account_balance = self.get_token_balance(target_owner) + 1u64;
-        self.set_token_balance(target_owner, updated_to_account_balance);
-        self.clear_approved(&token_identifier).ok();
+        Ok(())
+    }
+
+    fn parse_metadata_kinds(kinds: &[u8]) -> Option<Vec<NFTMetadataKind>> {
+        kinds
+            .iter()
+            .map(|kind| match kind {
+                0 => Some(NFTMetadataKind::CEP78),
+                1 => Some(NFTMetadataKind::NFT721),
+                2 => Some(NFTMetadataKind::Raw),
+                3 => Some(NFTMetadataKind::CustomValidated),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Asks a contract recipient whether it accepts the token. Accounts always accept.
+    fn check_on_cep78_received(
+        &self,
+        from: Option<Entity>,
+        target: Entity,
+        token_identifier: TokenIdentifier,
+        data: Vec<u8>,
+    ) -> Result<(), NFTCoreError> {
+        let Entity::Contract(address) = target else {
+            return Ok(());
+        };
+
+        let operator = casper::get_caller();
+        let acknowledged = ContractHandle::<CEP78ReceiverRef>::from_address(address)
+            .call(|receiver| receiver.on_cep78_received(operator, from, token_identifier, data));
+
+        match acknowledged {
+            Ok(true) => Ok(()),
+            Ok(false) | Err(_) => Err(NFTCoreError::ReceiverRejectedToken),
+        }
+    }
+
+    fn emit_burn_event(
+        &mut self,
+        token_owner: Entity,
+        token_identifier: TokenIdentifier,
+        burner: Entity,
+    ) {
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => {
+                self.emit_ces_event(Burn::new(token_owner, token_identifier, burner))
+            }
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::Burn {
+                owner: token_owner,
+                token_id: token_identifier,
+                burner,
+            }),
+        };
+    }
 
+    fn emit_transfer_event(
+        &mut self,
+        caller: Entity,
+        source_owner: Entity,
+        target_owner: Entity,
+        token_identifier: TokenIdentifier,
+    ) {
         match self.state.events_mode {
             EventsMode::NoEvents => {}
             EventsMode::CEP47 => self.write_cep47_event(CEP47Event::Transfer {
@@ -613,45 +1806,190 @@ impl NFTContract {
                 token_id: token_identifier,
             }),
             EventsMode::CES => {
-                let spender = if caller == owner {
+                let spender = if caller == source_owner {
                     None
                 } else {
                     Some(Self::unwrap_entity(caller))
                 };
                 self.emit_ces_event(Transfer::new(
-                    Self::unwrap_entity(owner),
+                    Self::unwrap_entity(source_owner),
                     spender,
                     Self::unwrap_entity(target_owner),
                     token_identifier,
                 ));
             }
         }
+    }
+
+    // Nothing can change hands while the contract is paused.
+    fn check_not_paused(&self) -> Result<(), NFTCoreError> {
+        if self.state.paused {
+            return Err(NFTCoreError::ContractIsPaused);
+        }
 
         Ok(())
     }
 
-    pub fn balance_of(&self, owner: Entity) -> Result<u64, NFTCoreError> {
-        let balance = self.get_token_balance(owner);
-        Ok(balance)
+    fn set_paused(&mut self, paused: bool) -> Result<(), NFTCoreError> {
+        let caller = casper::get_caller();
+        if !self.holds_role(caller, Role::Pauser) {
+            return Err(NFTCoreError::InvalidAccount);
+        }
+
+        self.state.paused = paused;
+
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => match paused {
+                true => self.emit_ces_event(Paused::new(caller)),
+                false => self.emit_ces_event(Unpaused::new(caller)),
+            },
+            EventsMode::CEP47 => self.write_cep47_event(match paused {
+                true => CEP47Event::Paused { account: caller },
+                false => CEP47Event::Unpaused { account: caller },
+            }),
+        }
+
+        Ok(())
     }
 
-    pub fn owner_of(&self, identifier: TokenIdentifier) -> Result<Entity, NFTCoreError> {
-        let number_of_minted_tokens = self.state.minted_tokens_count;
+    fn set_token_locked(
+        &mut self,
+        token_identifier: TokenIdentifier,
+        locked: bool,
+    ) -> Result<(), NFTCoreError> {
+        let token_identifier = self.resolve_token_identifier(token_identifier)?;
+        let caller = casper::get_caller();
 
-        // Revert if token_id is out of bounds
-        if let NFTIdentifierMode::Ordinal = self.state.identifier_mode {
-            if let TokenIdentifier::Ordinal(ord) = identifier {
-                if ord >= number_of_minted_tokens {
-                    return Err(NFTCoreError::InvalidTokenIdentifier);
-                }
+        let Some(mut data) = self.state.store.data.get(&token_identifier) else {
+            return Err(NFTCoreError::MissingTokenOwner);
+        };
+
+        // Admins can always (un)lock tokens, owners only if the collection allows it.
+        let is_admin = self.holds_role(caller, Role::Admin);
+        let is_owner = data.owner == Some(caller)
+            && TokenLockMode::AdminAndOwner == self.state.token_lock_mode;
+
+        if !is_admin && !is_owner {
+            return Err(NFTCoreError::InvalidAccount);
+        }
+
+        if self.read_token_burned(&token_identifier) {
+            return Err(NFTCoreError::PreviouslyBurntToken);
+        }
+
+        data.locked = locked;
+        self.state.store.data.insert(&token_identifier, &data);
+
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => match locked {
+                true => self.emit_ces_event(TokenLocked::new(token_identifier, caller)),
+                false => self.emit_ces_event(TokenUnlocked::new(token_identifier, caller)),
+            },
+            EventsMode::CEP47 => self.write_cep47_event(match locked {
+                true => CEP47Event::TokenLocked {
+                    token_id: token_identifier,
+                    locker: caller,
+                },
+                false => CEP47Event::TokenUnlocked {
+                    token_id: token_identifier,
+                    locker: caller,
+                },
+            }),
+        }
+
+        Ok(())
+    }
+
+    fn check_royalty(royalty: &Option<Royalty>) -> Result<(), NFTCoreError> {
+        match royalty {
+            Some(royalty) if royalty.basis_points > MAX_ROYALTY_BASIS_POINTS => {
+                Err(NFTCoreError::InvalidRoyalty)
             }
+            _ => Ok(()),
         }
+    }
 
-        let Some(owner) = self.read_token_owner(&identifier) else {
-            return Err(NFTCoreError::MissingTokenOwner);
+    fn insert_token_royalty(&mut self, token_identifier: &TokenIdentifier, royalty: Royalty) {
+        let mut data = self
+            .state
+            .store
+            .data
+            .get(token_identifier)
+            .unwrap_or_default();
+        data.royalty = Some(royalty);
+        self.state.store.data.insert(token_identifier, &data);
+    }
+
+    fn emit_approval_event(
+        &mut self,
+        owner: Entity,
+        spender: Entity,
+        token_identifier: TokenIdentifier,
+    ) {
+        let owner = Self::unwrap_entity(owner);
+        let spender = Self::unwrap_entity(spender);
+        match self.state.events_mode {
+            EventsMode::NoEvents => {}
+            EventsMode::CES => self.emit_ces_event(Approval::new(owner, spender, token_identifier)),
+            EventsMode::CEP47 => self.write_cep47_event(CEP47Event::ApprovalGranted {
+                owner,
+                spender,
+                token_id: token_identifier,
+            }),
         };
+    }
 
-        Ok(owner)
+    fn verify_permit_signature(
+        owner: Entity,
+        public_key: &[u8],
+        signature: &[u8],
+        message: &PermitMessage,
+    ) -> Result<(), NFTCoreError> {
+        let Entity::Account(account_hash) = owner else {
+            return Err(NFTCoreError::InvalidPermitSignature);
+        };
+
+        // Accounts are identified by the hash of their algorithm tagged public key.
+        let mut preimage = b"ed25519".to_vec();
+        preimage.push(0);
+        preimage.extend_from_slice(public_key);
+        let expected_account_hash = Params::new().hash_length(32).hash(&preimage);
+        if expected_account_hash.as_bytes() != account_hash {
+            return Err(NFTCoreError::InvalidPermitSignature);
+        }
+
+        let public_key: [u8; 32] = public_key
+            .try_into()
+            .map_err(|_| NFTCoreError::InvalidPermitSignature)?;
+        let signature: [u8; 64] = signature
+            .try_into()
+            .map_err(|_| NFTCoreError::InvalidPermitSignature)?;
+
+        let verifying_key = VerifyingKey::from_bytes(&public_key)
+            .map_err(|_| NFTCoreError::InvalidPermitSignature)?;
+        let message = borsh::to_vec(message).map_err(|_| NFTCoreError::InvalidPermitSignature)?;
+
+        verifying_key
+            .verify_strict(&message, &Signature::from_bytes(&signature))
+            .map_err(|_| NFTCoreError::InvalidPermitSignature)
+    }
+
+    fn is_installer(&self, entity: Entity) -> bool {
+        self.state.installer == Some(entity)
+    }
+
+    // The installer and admins implicitly hold every role.
+    fn holds_role(&self, entity: Entity, role: Role) -> bool {
+        if self.is_installer(entity) {
+            return true;
+        }
+
+        match self.state.store.entity_data.get(&entity) {
+            Some(data) => data.roles.contains(&Role::Admin) || data.roles.contains(&role),
+            None => false,
+        }
     }
 
     fn unwrap_entity(entity: Entity) -> Address {
@@ -661,17 +1999,13 @@ impl NFTContract {
         }
     }
 
-    fn caller_is_operator_for_owner(&self, caller: Entity, owner: Entity) -> bool {
-        for entry in &self.state.store.operators {
-            if entry.key == owner && entry.value == caller {
-                return true;
-            }
-        }
-
-        return false;
-    }
-
-    fn set_operator_for_owner(&mut self, owner: Entity, operator: Entity, value: bool) {
+    fn set_operator_for_owner(
+        &mut self,
+        owner: Entity,
+        operator: Entity,
+        value: bool,
+        expires_at: Option<u64>,
+    ) {
         if value == false {
             self.state.store.operators.retain(|entry| {
                 let owned = entry.key == owner;
@@ -682,11 +2016,13 @@ impl NFTContract {
             return;
         }
 
-        for entry in &self.state.store.operators {
+        for entry in &mut self.state.store.operators {
             let owned = entry.key == owner;
             let is_operator = entry.value == operator;
             let operator_for_owner = owned && is_operator;
             if operator_for_owner {
+                // Granting again only refreshes the expiry.
+                entry.expires_at = expires_at;
                 return;
             }
         }
@@ -694,39 +2030,100 @@ impl NFTContract {
         self.state.store.operators.push(OperatorEntry {
             key: owner,
             value: operator,
+            expires_at,
         });
     }
 
     fn clear_approved(&mut self, token_identifier: &TokenIdentifier) -> Result<(), NFTCoreError> {
         if let Some(mut data) = self.state.store.data.get(token_identifier) {
-            data.approved = None;
+            data.approved.clear();
+            self.state.store.data.insert(token_identifier, &data);
         }
         Ok(())
     }
 
-    fn set_approved(
+    fn remove_approved(
         &mut self,
         token_identifier: &TokenIdentifier,
-        entity: Entity,
+        spender: Entity,
     ) -> Result<(), NFTCoreError> {
-        if let Some(mut data) = self.state.store.data.get(token_identifier) {
-            data.approved = Some(entity);
-        } else {
+        let Some(mut data) = self.state.store.data.get(token_identifier) else {
             return Err(NFTCoreError::InvalidTokenIdentifier);
+        };
+
+        let approved_count = data.approved.len();
+        data.approved.retain(|approved| approved.spender != spender);
+        if data.approved.len() == approved_count {
+            return Err(NFTCoreError::InvalidSpenderAccountHash);
         }
 
+        self.state.store.data.insert(token_identifier, &data);
         Ok(())
     }
 
-    fn get_approved(
+    fn set_approved(
         &mut self,
         token_identifier: &TokenIdentifier,
-    ) -> Result<Option<Entity>, NFTCoreError> {
-        if let Some(data) = self.state.store.data.get(token_identifier) {
-            Ok(data.approved)
-        } else {
-            Err(NFTCoreError::InvalidTokenIdentifier)
+        entity: Entity,
+        expires_at: Option<u64>,
+    ) -> Result<(), NFTCoreError> {
+        let Some(mut data) = self.state.store.data.get(token_identifier) else {
+            return Err(NFTCoreError::InvalidTokenIdentifier);
+        };
+
+        // Expired approvals shouldn't take up one of the limited slots.
+        data.approved
+            .retain(|approved| !Self::is_expired(approved.expires_at));
+
+        match data
+            .approved
+            .iter_mut()
+            .find(|approved| approved.spender == entity)
+        {
+            // Approving again only refreshes the expiry.
+            Some(approved) => approved.expires_at = expires_at,
+            None => {
+                if data.approved.len() >= MAX_APPROVED_SPENDERS {
+                    return Err(NFTCoreError::TooManyApprovedSpenders);
+                }
+                data.approved.push(ApprovedSpender {
+                    spender: entity,
+                    expires_at,
+                });
+            }
+        }
+
+        self.state.store.data.insert(token_identifier, &data);
+        Ok(())
+    }
+
+    fn clear_user(&mut self, token_identifier: &TokenIdentifier) {
+        if let Some(mut data) = self.state.store.data.get(token_identifier) {
+            if data.user.is_some() {
+                data.user = None;
+                self.state.store.data.insert(token_identifier, &data);
+            }
+        }
+    }
+
+    fn is_approved_spender(&self, token_identifier: &TokenIdentifier, entity: Entity) -> bool {
+        match self.state.store.data.get(token_identifier) {
+            // Expired approvals are treated as if they were never granted.
+            Some(data) => data.approved.iter().any(|approved| {
+                approved.spender == entity && !Self::is_expired(approved.expires_at)
+            }),
+            None => false,
+        }
+    }
+
+    fn decrease_token_balance(&mut self, owner: Entity, amount: u64) -> Result<(), NFTCoreError> {
+        let balance = self.get_token_balance(owner);
+        if balance < amount {
+            // This should never happen...
+            return Err(NFTCoreError::FatalTokenIdDuplication);
         }
+        self.set_token_balance(owner, balance - amount);
+        Ok(())
     }
 
     fn set_token_balance(&mut self, owner: Entity, count: u64) {
@@ -747,6 +2144,18 @@ impl NFTContract {
         self.state.store.burned_tokens.push(token_identifier);
     }
 
+    fn get_circulating_supply(&self) -> u64 {
+        let burned_tokens_count = self.state.store.burned_tokens.len() as u64;
+        self.state.minted_tokens_count - burned_tokens_count
+    }
+
+    fn read_token_locked(&self, token_identifier: &TokenIdentifier) -> bool {
+        match self.state.store.data.get(token_identifier) {
+            Some(data) => data.locked,
+            None => false,
+        }
+    }
+
     fn read_token_burned(&self, token_identifier: &TokenIdentifier) -> bool {
         self.state.store.burned_tokens.contains(token_identifier)
     }
@@ -758,12 +2167,19 @@ impl NFTContract {
             let is_operator = entry.value == caller;
             let operator_for_owner = owned && is_operator;
             if operator_for_owner {
-                return true;
+                return !Self::is_expired(entry.expires_at);
             }
         }
         false
     }
 
+    fn is_expired(expires_at: Option<u64>) -> bool {
+        match expires_at {
+            Some(expires_at) => casper::get_block_time() >= expires_at,
+            None => false,
+        }
+    }
+
     fn insert_hash_id_lookups(
         &mut self,
         token_identifier: &TokenIdentifier,
@@ -798,18 +2214,22 @@ impl NFTContract {
         Ok(())
     }
 
-    fn insert_metadata(&mut self, identifier: &TokenIdentifier, metadata: &String) {
-        self.state.store.metadata.insert(identifier, metadata);
+    fn insert_metadata(&mut self, identifier: &TokenIdentifier, metadata: &str) {
+        self.state
+            .store
+            .metadata
+            .insert(identifier, &metadata.to_string());
     }
 
     fn insert_token_issuer(&mut self, token_identifier: &TokenIdentifier, issuer: Entity) {
-        if let Some(mut data) = self.state.store.data.get(&token_identifier) {
-            data.issuer = Some(issuer);
-        } else {
-            let mut data = TokenData::default();
-            data.issuer = Some(issuer);
-            self.state.store.data.insert(&token_identifier, &data);
-        }
+        let mut data = self
+            .state
+            .store
+            .data
+            .get(token_identifier)
+            .unwrap_or_default();
+        data.issuer = Some(issuer);
+        self.state.store.data.insert(token_identifier, &data);
     }
 
     fn read_token_owner(&self, token_identifier: &TokenIdentifier) -> Option<Entity> {
@@ -821,13 +2241,14 @@ impl NFTContract {
     }
 
     fn insert_token_owner(&mut self, token_identifier: &TokenIdentifier, owner: Entity) {
-        if let Some(mut data) = self.state.store.data.get(&token_identifier) {
-            data.owner = Some(owner);
-        } else {
-            let mut data = TokenData::default();
-            data.owner = Some(owner);
-            self.state.store.data.insert(&token_identifier, &data);
-        }
+        let mut data = self
+            .state
+            .store
+            .data
+            .get(token_identifier)
+            .unwrap_or_default();
+        data.owner = Some(owner);
+        self.state.store.data.insert(token_identifier, &data);
     }
 
     fn validate_metadata(
@@ -973,7 +2394,15 @@ impl NFTContract {
     }
 
     fn generate_hash(&self, metadata: String) -> String {
-        base16::encode_lower(&blake2b(metadata.as_bytes()))
+        match self.state.hash_algorithm {
+            HashAlgorithm::Blake2b256 => base16::encode_lower(
+                Params::new()
+                    .hash_length(32)
+                    .hash(metadata.as_bytes())
+                    .as_bytes(),
+            ),
+            HashAlgorithm::Sha256 => base16::encode_lower(&Sha256::digest(metadata.as_bytes())),
+        }
     }
 
     fn is_whitelisted(&self, key: Entity) -> bool {
@@ -984,9 +2413,15 @@ impl NFTContract {
         }
     }
 
+    // Keeps the whitelist and the entity data in sync.
     fn insert_acl_entry(&mut self, key: Entity, access: bool) {
-        if let Some(mut data) = self.state.store.entity_data.get(&key) {
-            data.whitelisted = access;
+        let mut data = self.state.store.entity_data.get(&key).unwrap_or_default();
+        data.whitelisted = access;
+        self.state.store.entity_data.insert(&key, &data);
+
+        self.state.acl_whitelist.retain(|entity| *entity != key);
+        if access {
+            self.state.acl_whitelist.push(key);
         }
     }
 
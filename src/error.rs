@@ -178,4 +178,13 @@ pub enum NFTCoreError {
     MissingOperatorBurnMode,                     // 168
     InvalidIdentifier,                           // 169
     DuplicateIdentifier,                         // 170
+    ReceiverRejectedToken,                       // 171
+    ContractIsPaused,                            // 172
+    TokenIsLocked,                               // 173
+    InvalidRoyalty,                              // 174
+    PermitExpired,                               // 175
+    InvalidPermitNonce,                          // 176
+    InvalidPermitSignature,                      // 177
+    InvalidExpiration,                           // 178
+    TooManyApprovedSpenders,                     // 179
 }
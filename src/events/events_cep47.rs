@@ -2,7 +2,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use casper_macros::CasperABI;
 use casper_sdk::{casper::Entity, types::Address};
 
-use crate::types::TokenIdentifier;
+use crate::types::{Role, TokenIdentifier};
 
 #[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
 #[borsh(use_discriminant = true)]
@@ -23,6 +23,7 @@ pub enum CEP47Event {
     },
     ApprovalRevoked {
         owner: Address,
+        spender: Option<Address>,
         token_id: TokenIdentifier,
     },
     ApprovalForAll {
@@ -43,4 +44,50 @@ pub enum CEP47Event {
     },
     VariablesSet,
     Migrate,
+    AdminTransferStarted {
+        current_admin: Entity,
+        pending_admin: Entity,
+    },
+    AdminTransferred {
+        previous_admin: Entity,
+        new_admin: Entity,
+    },
+    AdminRenounced {
+        previous_admin: Entity,
+    },
+    RoleGranted {
+        role: Role,
+        account: Entity,
+        sender: Entity,
+    },
+    RoleRevoked {
+        role: Role,
+        account: Entity,
+        sender: Entity,
+    },
+    Paused {
+        account: Entity,
+    },
+    Unpaused {
+        account: Entity,
+    },
+    TokenLocked {
+        token_id: TokenIdentifier,
+        locker: Entity,
+    },
+    TokenUnlocked {
+        token_id: TokenIdentifier,
+        locker: Entity,
+    },
+    TokenRecovered {
+        token_id: TokenIdentifier,
+        previous_owner: Entity,
+        new_owner: Entity,
+        issuer: Entity,
+    },
+    UpdateUser {
+        token_id: TokenIdentifier,
+        user: Option<Entity>,
+        expires_at: u64,
+    },
 }
@@ -1,6 +1,6 @@
 use casper_sdk::{casper::Entity, types::Address};
 
-use crate::types::TokenIdentifier;
+use crate::types::{Role, TokenIdentifier};
 
 pub trait Event {}
 
@@ -14,6 +14,17 @@ impl Event for Transfer {}
 impl Event for MetadataUpdated {}
 impl Event for VariablesSet {}
 impl Event for Migration {}
+impl Event for AdminTransferStarted {}
+impl Event for AdminTransferred {}
+impl Event for AdminRenounced {}
+impl Event for RoleGranted {}
+impl Event for RoleRevoked {}
+impl Event for Paused {}
+impl Event for Unpaused {}
+impl Event for TokenLocked {}
+impl Event for TokenUnlocked {}
+impl Event for TokenRecovered {}
+impl Event for UpdateUser {}
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Mint {
@@ -69,13 +80,16 @@ impl Approval {
 #[derive(Debug, PartialEq, Eq)]
 pub struct ApprovalRevoked {
     owner: Address,
+    // `None` when every approval of the token was revoked.
+    spender: Option<Address>,
     token_id: String,
 }
 
 impl ApprovalRevoked {
-    pub fn new(owner: Address, token_id: TokenIdentifier) -> Self {
+    pub fn new(owner: Address, spender: Option<Address>, token_id: TokenIdentifier) -> Self {
         Self {
             owner,
+            spender,
             token_id: token_id.to_string(),
         }
     }
@@ -161,3 +175,171 @@ impl Migration {
         Self {}
     }
 }
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AdminTransferStarted {
+    current_admin: Entity,
+    pending_admin: Entity,
+}
+
+impl AdminTransferStarted {
+    pub fn new(current_admin: Entity, pending_admin: Entity) -> Self {
+        Self {
+            current_admin,
+            pending_admin,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AdminTransferred {
+    previous_admin: Entity,
+    new_admin: Entity,
+}
+
+impl AdminTransferred {
+    pub fn new(previous_admin: Entity, new_admin: Entity) -> Self {
+        Self {
+            previous_admin,
+            new_admin,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AdminRenounced {
+    previous_admin: Entity,
+}
+
+impl AdminRenounced {
+    pub fn new(previous_admin: Entity) -> Self {
+        Self { previous_admin }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RoleGranted {
+    role: Role,
+    account: Entity,
+    sender: Entity,
+}
+
+impl RoleGranted {
+    pub fn new(role: Role, account: Entity, sender: Entity) -> Self {
+        Self {
+            role,
+            account,
+            sender,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RoleRevoked {
+    role: Role,
+    account: Entity,
+    sender: Entity,
+}
+
+impl RoleRevoked {
+    pub fn new(role: Role, account: Entity, sender: Entity) -> Self {
+        Self {
+            role,
+            account,
+            sender,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Paused {
+    account: Entity,
+}
+
+impl Paused {
+    pub fn new(account: Entity) -> Self {
+        Self { account }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Unpaused {
+    account: Entity,
+}
+
+impl Unpaused {
+    pub fn new(account: Entity) -> Self {
+        Self { account }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TokenLocked {
+    token_id: String,
+    locker: Entity,
+}
+
+impl TokenLocked {
+    pub fn new(token_id: TokenIdentifier, locker: Entity) -> Self {
+        Self {
+            token_id: token_id.to_string(),
+            locker,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TokenUnlocked {
+    token_id: String,
+    locker: Entity,
+}
+
+impl TokenUnlocked {
+    pub fn new(token_id: TokenIdentifier, locker: Entity) -> Self {
+        Self {
+            token_id: token_id.to_string(),
+            locker,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TokenRecovered {
+    token_id: String,
+    previous_owner: Entity,
+    new_owner: Entity,
+    issuer: Entity,
+}
+
+impl TokenRecovered {
+    pub fn new(
+        token_id: TokenIdentifier,
+        previous_owner: Entity,
+        new_owner: Entity,
+        issuer: Entity,
+    ) -> Self {
+        Self {
+            token_id: token_id.to_string(),
+            previous_owner,
+            new_owner,
+            issuer,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UpdateUser {
+    token_id: String,
+    user: Option<Entity>,
+    expires_at: u64,
+}
+
+impl UpdateUser {
+    pub fn new(token_id: TokenIdentifier, user: Option<Entity>, expires_at: u64) -> Self {
+        Self {
+            token_id: token_id.to_string(),
+            user,
+            expires_at,
+        }
+    }
+}
@@ -1,9 +1,12 @@
 #[macro_use]
 extern crate alloc;
 
+pub mod config;
 pub mod contract;
 pub mod error;
 pub mod events;
+pub mod receiver;
 #[cfg(test)]
 pub mod tests;
+pub mod transfer_filter;
 pub mod types;
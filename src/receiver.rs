@@ -0,0 +1,19 @@
+use casper_macros::casper;
+use casper_sdk::casper::Entity;
+
+use crate::types::TokenIdentifier;
+
+/// Interface a contract has to implement to receive tokens through `safe_transfer` and
+/// `safe_mint`. Returning `false` (or failing) reverts the whole operation.
+#[casper(trait_definition)]
+pub trait CEP78Receiver {
+    /// Called after `token_id` was moved to the implementing contract. `operator` is the entity
+    /// which initiated the call and `from` the previous owner, or `None` if the token was minted.
+    fn on_cep78_received(
+        &mut self,
+        operator: Entity,
+        from: Option<Entity>,
+        token_id: TokenIdentifier,
+        data: Vec<u8>,
+    ) -> bool;
+}
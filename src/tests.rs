@@ -1,17 +1,143 @@
-use casper_sdk::casper::{
-    self,
-    native::{Environment, DEFAULT_ADDRESS},
-    Entity,
+use blake2b_simd::Params;
+use casper_macros::casper;
+use casper_sdk::{
+    casper::{
+        self,
+        native::{Environment, DEFAULT_ADDRESS},
+        Entity,
+    },
+    collections::Map,
+    ContractBuilder,
 };
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
 
 use crate::{
+    config::InstallConfig,
     contract::NFTContract,
+    error::NFTCoreError,
+    receiver::CEP78Receiver,
+    transfer_filter::CEP78TransferFilter,
     types::{
-        BurnMode, MetadataMutability, MintingMode, NFTIdentifierMode, NFTKind, NFTMetadataKind,
-        OwnershipMode, WhitelistMode,
+        BaselineEntityData, BaselineState, BaselineStateStore, BaselineTokenData, BurnMode,
+        EventsMode, HashAlgorithm, MetadataMutability, MigrationRecord, MintEntry, MintingMode,
+        NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode, PermitMessage, Role, Royalty,
+        RoyaltyPayment, TokenIdentifier, TransferFilterContractResult, WhitelistMode,
+        MAX_APPROVED_SPENDERS, PERMIT_DOMAIN, STATE_VERSION,
     },
 };
 
+fn install_contract() -> NFTContract {
+    NFTContract::new(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .build(),
+    )
+}
+
+// Receiver contract used to exercise `safe_mint` and `safe_transfer`.
+#[casper(contract_state)]
+#[derive(Default)]
+struct TestReceiver {
+    accept: bool,
+}
+
+#[casper(contract)]
+impl TestReceiver {
+    #[casper(constructor)]
+    pub fn new(accept: bool) -> Self {
+        Self { accept }
+    }
+}
+
+#[casper(path = crate::receiver)]
+impl CEP78Receiver for TestReceiver {
+    fn on_cep78_received(
+        &mut self,
+        _operator: Entity,
+        _from: Option<Entity>,
+        _token_id: TokenIdentifier,
+        _data: Vec<u8>,
+    ) -> bool {
+        self.accept
+    }
+}
+
+// Account of an ed25519 key, derived the same way as the node does.
+fn account_of(signing_key: &SigningKey) -> Entity {
+    let mut preimage = b"ed25519".to_vec();
+    preimage.push(0);
+    preimage.extend_from_slice(signing_key.verifying_key().as_bytes());
+    let account_hash = Params::new().hash_length(32).hash(&preimage);
+    Entity::Account(account_hash.as_bytes().try_into().unwrap())
+}
+
+fn sign_permit(
+    signing_key: &SigningKey,
+    spender: Entity,
+    token_identifier: TokenIdentifier,
+    deadline: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    let message = PermitMessage {
+        domain: PERMIT_DOMAIN.into(),
+        contract: casper::get_callee(),
+        package_hash: "".into(),
+        chain_name: "casper".into(),
+        owner: account_of(signing_key),
+        spender,
+        token_identifier,
+        deadline,
+        nonce,
+    };
+    let message = borsh::to_vec(&message).unwrap();
+    signing_key.sign(&message).to_bytes().to_vec()
+}
+
+fn deploy_receiver(accept: bool) -> Entity {
+    let receiver = ContractBuilder::<TestReceiverRef>::new()
+        .create(|| TestReceiverRef::new(accept))
+        .unwrap();
+    Entity::Contract(receiver.contract_address())
+}
+
+// Transfer filter contract which either lets every transfer through or denies all of them.
+#[casper(contract_state)]
+#[derive(Default)]
+struct TestTransferFilter {
+    allow: bool,
+}
+
+#[casper(contract)]
+impl TestTransferFilter {
+    #[casper(constructor)]
+    pub fn new(allow: bool) -> Self {
+        Self { allow }
+    }
+}
+
+#[casper(path = crate::transfer_filter)]
+impl CEP78TransferFilter for TestTransferFilter {
+    fn can_transfer(
+        &self,
+        _source_owner: Entity,
+        _target_owner: Entity,
+        _token_id: TokenIdentifier,
+    ) -> TransferFilterContractResult {
+        match self.allow {
+            true => TransferFilterContractResult::ProceedTransfer,
+            false => TransferFilterContractResult::DenyTransfer,
+        }
+    }
+}
+
+fn deploy_transfer_filter(allow: bool) -> Entity {
+    let transfer_filter = ContractBuilder::<TestTransferFilterRef>::new()
+        .create(|| TestTransferFilterRef::new(allow))
+        .unwrap();
+    Entity::Contract(transfer_filter.contract_address())
+}
+
 #[test]
 fn should_transfer_token() {
     let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
@@ -19,28 +145,7 @@ fn should_transfer_token() {
         let installer = casper::get_caller();
         let recipient = Entity::Account([1; 32]);
 
-        let mut contract = NFTContract::new(
-            "test-collection".into(),
-            "tc".into(),
-            100,
-            true,
-            MintingMode::Installer,
-            OwnershipMode::Transferable,
-            NFTKind::Virtual,
-            WhitelistMode::Unlocked,
-            Vec::new(),
-            false,
-            false,
-            "".into(),
-            NFTMetadataKind::Raw,
-            Vec::new(),
-            Vec::new(),
-            NFTIdentifierMode::Ordinal,
-            MetadataMutability::Immutable,
-            BurnMode::Burnable,
-            false,
-            None,
-        );
+        let mut contract = install_contract();
 
         assert_eq!(contract.balance_of(installer).unwrap(), 0);
         assert_eq!(contract.balance_of(recipient).unwrap(), 0);
@@ -60,3 +165,1691 @@ fn should_transfer_token() {
     });
     assert!(result.is_ok());
 }
+
+#[test]
+fn should_batch_mint_tokens() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+
+        let tokens = vec![
+            MintEntry {
+                token_metadata: "first".into(),
+                token_owner: installer,
+                optional_token_hash: None,
+                royalty: None,
+            },
+            MintEntry {
+                token_metadata: "second".into(),
+                token_owner: recipient,
+                optional_token_hash: None,
+                royalty: None,
+            },
+            MintEntry {
+                token_metadata: "third".into(),
+                token_owner: recipient,
+                optional_token_hash: None,
+                royalty: None,
+            },
+        ];
+
+        let minted_tokens = contract.batch_mint(tokens).unwrap();
+
+        assert_eq!(minted_tokens.len(), 3);
+        assert_eq!(contract.balance_of(installer).unwrap(), 1);
+        assert_eq!(contract.balance_of(recipient).unwrap(), 2);
+        assert_eq!(
+            contract.owner_of(minted_tokens[2].clone()).unwrap(),
+            recipient
+        );
+    });
+    assert!(result.is_ok());
+}
+
+fn mint_entry(token_metadata: &str, token_owner: Entity, token_hash: Option<&str>) -> MintEntry {
+    MintEntry {
+        token_metadata: token_metadata.into(),
+        token_owner,
+        optional_token_hash: token_hash.map(Into::into),
+        royalty: None,
+    }
+}
+
+#[test]
+fn should_not_batch_mint_beyond_total_supply() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .total_token_supply(2)
+                .build(),
+        );
+
+        let tokens = vec![
+            mint_entry("first", installer, None),
+            mint_entry("second", installer, None),
+            mint_entry("third", installer, None),
+        ];
+        assert_eq!(
+            contract.batch_mint(tokens.clone()),
+            Err(NFTCoreError::TokenSupplyDepleted)
+        );
+        assert_eq!(contract.balance_of(installer).unwrap(), 0);
+
+        // Nothing was minted, so the whole supply is still available.
+        assert_eq!(
+            contract.batch_mint(tokens[..2].to_vec()).unwrap(),
+            vec![TokenIdentifier::Ordinal(0), TokenIdentifier::Ordinal(1)]
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_not_batch_mint_anything_when_an_entry_is_invalid() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let valid_metadata = r#"{"name":"first","token_uri":"https://x","checksum":"abc"}"#;
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::CEP78)
+                .build(),
+        );
+
+        let tokens = vec![
+            mint_entry(valid_metadata, installer, None),
+            mint_entry("not cep78 metadata", installer, None),
+            mint_entry(valid_metadata, installer, None),
+        ];
+        assert_eq!(
+            contract.batch_mint(tokens),
+            Err(NFTCoreError::FailedToParseCep99Metadata)
+        );
+        assert_eq!(contract.balance_of(installer).unwrap(), 0);
+        assert_eq!(
+            contract.mint(valid_metadata.into(), installer, None),
+            Ok(TokenIdentifier::Ordinal(0))
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_not_batch_mint_duplicate_hashes() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let token_hash = "a".repeat(64);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .identifier_mode(NFTIdentifierMode::Hash)
+                .build(),
+        );
+
+        let tokens = vec![
+            mint_entry("first", installer, Some(&token_hash)),
+            mint_entry("second", installer, Some(&token_hash)),
+        ];
+        assert_eq!(
+            contract.batch_mint(tokens),
+            Err(NFTCoreError::DuplicateIdentifier)
+        );
+        assert_eq!(contract.balance_of(installer).unwrap(), 0);
+
+        // The hash wasn't registered by the rejected batch.
+        contract
+            .mint("first".into(), installer, Some(token_hash))
+            .unwrap();
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_validate_minted_metadata_against_base_metadata_kind() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::CEP78)
+                .build(),
+        );
+
+        assert_eq!(
+            contract.mint("not cep78 metadata".into(), installer, None),
+            Err(NFTCoreError::FailedToParseCep99Metadata)
+        );
+        contract
+            .mint(
+                r#"{"name":"first","token_uri":"https://x","checksum":"abc"}"#.into(),
+                installer,
+                None,
+            )
+            .unwrap();
+        assert_eq!(contract.balance_of(installer).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_batch_transfer_tokens() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+
+        let first_token = contract.mint("first".into(), installer, None).unwrap();
+        let second_token = contract.mint("second".into(), installer, None).unwrap();
+
+        contract
+            .batch_transfer(
+                installer,
+                recipient,
+                vec![first_token, second_token.clone()],
+            )
+            .unwrap();
+
+        assert_eq!(contract.balance_of(installer).unwrap(), 0);
+        assert_eq!(contract.balance_of(recipient).unwrap(), 2);
+        assert_eq!(contract.owner_of(second_token).unwrap(), recipient);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_not_batch_transfer_anything_when_a_token_is_rejected() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+
+        let first_token = contract.mint("first".into(), installer, None).unwrap();
+        let foreign_token = contract.mint("second".into(), recipient, None).unwrap();
+
+        assert_eq!(
+            contract.batch_transfer(
+                installer,
+                recipient,
+                vec![first_token.clone(), foreign_token.clone()],
+            ),
+            Err(NFTCoreError::InvalidAccount)
+        );
+        assert_eq!(
+            contract.batch_transfer(
+                installer,
+                recipient,
+                vec![first_token.clone(), first_token.clone()],
+            ),
+            Err(NFTCoreError::DuplicateIdentifier)
+        );
+
+        assert_eq!(contract.balance_of(installer).unwrap(), 1);
+        assert_eq!(contract.balance_of(recipient).unwrap(), 1);
+        assert_eq!(contract.owner_of(first_token).unwrap(), installer);
+        assert_eq!(contract.owner_of(foreign_token).unwrap(), recipient);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_batch_burn_tokens() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = install_contract();
+        let first_token = contract.mint("first".into(), installer, None).unwrap();
+        let second_token = contract.mint("second".into(), installer, None).unwrap();
+        contract.mint("third".into(), installer, None).unwrap();
+
+        contract
+            .batch_burn(vec![first_token, second_token])
+            .unwrap();
+
+        assert_eq!(contract.balance_of(installer).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_burn_nothing_if_any_token_in_batch_fails() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let first_token = contract.mint("first".into(), installer, None).unwrap();
+        let foreign_token = contract.mint("second".into(), recipient, None).unwrap();
+
+        assert_eq!(
+            contract.batch_burn(vec![first_token.clone(), first_token.clone()]),
+            Err(NFTCoreError::DuplicateIdentifier)
+        );
+        assert_eq!(
+            contract.batch_burn(vec![first_token.clone(), foreign_token]),
+            Err(NFTCoreError::InvalidTokenOwner)
+        );
+
+        assert_eq!(contract.balance_of(installer).unwrap(), 1);
+        assert_eq!(contract.balance_of(recipient).unwrap(), 1);
+        contract.burn(first_token).unwrap();
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_safe_mint_and_transfer_to_accepting_receiver() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let receiver = deploy_receiver(true);
+
+        let mut contract = install_contract();
+        let minted_token = contract
+            .safe_mint("first".into(), receiver, None, Vec::new())
+            .unwrap();
+        assert_eq!(contract.owner_of(minted_token).unwrap(), receiver);
+
+        let minted_token = contract.mint("second".into(), installer, None).unwrap();
+        contract
+            .safe_transfer(installer, receiver, minted_token.clone(), Vec::new())
+            .unwrap();
+        assert_eq!(contract.owner_of(minted_token).unwrap(), receiver);
+        assert_eq!(contract.balance_of(receiver).unwrap(), 2);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_revert_safe_mint_rejected_by_receiver() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let receiver = deploy_receiver(false);
+
+        let mut contract = install_contract();
+        let _ = contract.safe_mint("first".into(), receiver, None, Vec::new());
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn should_revert_safe_transfer_rejected_by_receiver() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let receiver = deploy_receiver(false);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+        let _ = contract.safe_transfer(installer, receiver, minted_token, Vec::new());
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn should_deny_transfer_rejected_by_transfer_filter() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .transfer_filter_contract(deploy_transfer_filter(false))
+                .build(),
+        );
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        assert_eq!(
+            contract.transfer(installer, recipient, minted_token.clone()),
+            Err(NFTCoreError::TransferFilterContractDenied)
+        );
+        assert_eq!(contract.owner_of(minted_token).unwrap(), installer);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_transfer_when_transfer_filter_allows() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .transfer_filter_contract(deploy_transfer_filter(true))
+                .build(),
+        );
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        contract
+            .transfer(installer, recipient, minted_token.clone())
+            .unwrap();
+        assert_eq!(contract.owner_of(minted_token).unwrap(), recipient);
+    });
+    assert!(result.is_ok());
+}
+
+fn assert_install_rejected(config: InstallConfig, expected: NFTCoreError) {
+    assert_eq!(
+        NFTContract::check_install_configuration(&config),
+        Err(expected)
+    );
+}
+
+#[test]
+fn should_reject_install_with_zero_supply() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .total_token_supply(0)
+            .build(),
+        NFTCoreError::CannotInstallWithZeroSupply,
+    );
+}
+
+#[test]
+fn should_reject_install_with_empty_collection_name() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .collection_name("")
+            .build(),
+        NFTCoreError::InvalidCollectionName,
+    );
+}
+
+#[test]
+fn should_reject_install_with_empty_collection_symbol() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .collection_symbol("")
+            .build(),
+        NFTCoreError::InvalidCollectionSymbol,
+    );
+}
+
+#[test]
+fn should_reject_acl_install_with_empty_whitelist() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .minting_mode(MintingMode::Acl)
+            .build(),
+        NFTCoreError::EmptyACLWhitelist,
+    );
+}
+
+#[test]
+fn should_reject_mutable_metadata_in_hash_mode() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .identifier_mode(NFTIdentifierMode::Hash)
+            .metadata_mutability(MetadataMutability::Mutable)
+            .build(),
+        NFTCoreError::InvalidMetadataMutability,
+    );
+}
+
+#[test]
+fn should_reject_unknown_optional_metadata_kind() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .optional_metadata(vec![4])
+            .build(),
+        NFTCoreError::InvalidOptionalMetadata,
+    );
+}
+
+#[test]
+fn should_reject_custom_validated_metadata_without_schema() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::CustomValidated)
+            .build(),
+        NFTCoreError::MissingJsonSchema,
+    );
+}
+
+#[test]
+fn should_reject_invalid_json_schema() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::CustomValidated)
+            .json_schema("not a schema")
+            .build(),
+        NFTCoreError::InvalidJsonSchema,
+    );
+}
+
+#[test]
+fn should_reject_transfer_filter_for_non_transferable_tokens() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .ownership_mode(OwnershipMode::Minter)
+            .transfer_filter_contract(Entity::Contract([1; 32]))
+            .build(),
+        NFTCoreError::TransferFilterContractNeedsTransferableMode,
+    );
+}
+
+#[test]
+fn should_reject_account_as_transfer_filter() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .transfer_filter_contract(Entity::Account([1; 32]))
+            .build(),
+        NFTCoreError::InvalidTransferFilterContract,
+    );
+}
+
+#[test]
+fn should_reject_default_royalty_above_sale_price() {
+    assert_install_rejected(
+        InstallConfig::builder()
+            .base_metadata_kind(NFTMetadataKind::Raw)
+            .default_royalty(Entity::Account([1; 32]), 10_001)
+            .build(),
+        NFTCoreError::InvalidRoyalty,
+    );
+}
+
+#[test]
+fn should_mint_as_whitelisted_entity_in_acl_mode() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .minting_mode(MintingMode::Acl)
+                .acl_whitelist(vec![installer])
+                .build(),
+        );
+
+        contract.mint("first".into(), installer, None).unwrap();
+        assert_eq!(contract.balance_of(installer).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_drop_entities_left_out_of_a_replaced_acl_whitelist() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let other = Entity::Account([1; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .minting_mode(MintingMode::Acl)
+                .acl_whitelist(vec![installer])
+                .build(),
+        );
+        contract.mint("first".into(), installer, None).unwrap();
+
+        contract
+            .set_variables(None, None, None, None, Some(vec![other]), None, None)
+            .unwrap();
+        assert_eq!(
+            contract.mint("second".into(), installer, None),
+            Err(NFTCoreError::InvalidMinter)
+        );
+        assert_eq!(contract.balance_of(installer).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_report_collection_info() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = install_contract();
+        contract.mint("first".into(), installer, None).unwrap();
+
+        let info = contract.collection_info().unwrap();
+        assert_eq!(info.collection_name, "test-collection");
+        assert_eq!(info.total_token_supply, Some(100));
+        assert_eq!(info.minted_tokens_count, 1);
+        assert_eq!(contract.number_of_minted_tokens().unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_exclude_burnt_tokens_from_circulating_supply() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = install_contract();
+        let first_token = contract.mint("first".into(), installer, None).unwrap();
+        contract.mint("second".into(), installer, None).unwrap();
+
+        assert_eq!(contract.circulating_supply().unwrap(), 2);
+
+        contract.burn(first_token).unwrap();
+
+        assert_eq!(contract.circulating_supply().unwrap(), 1);
+        assert_eq!(contract.number_of_minted_tokens().unwrap(), 2);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_transfer_admin_in_two_steps() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let new_admin = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        contract.transfer_admin(new_admin).unwrap();
+
+        // The role only moves once the new admin accepts it.
+        assert_eq!(contract.admin().unwrap(), Some(installer));
+        assert!(contract.accept_admin().is_err());
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_hand_over_admin_once_accepted() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let new_admin = Entity::Account([1; 32]);
+
+    let mut contract = casper::native::dispatch_with(stub.clone(), || {
+        let mut contract = install_contract();
+        contract.transfer_admin(new_admin).unwrap();
+        contract
+    })
+    .unwrap();
+
+    let result = casper::native::dispatch_with(stub.clone().with_caller(new_admin), || {
+        contract.accept_admin().unwrap();
+        assert_eq!(contract.admin().unwrap(), Some(new_admin));
+        contract
+            .set_variables(Some(false), None, None, None, None, None, None)
+            .unwrap();
+    });
+    assert!(result.is_ok());
+
+    // The previous admin lost every admin power.
+    let result = casper::native::dispatch_with(stub, || {
+        assert_eq!(
+            contract.set_variables(Some(true), None, None, None, None, None, None),
+            Err(NFTCoreError::InvalidAccount)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_renounce_admin() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let pending_admin = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        contract.transfer_admin(pending_admin).unwrap();
+        contract.renounce_admin().unwrap();
+
+        assert_eq!(contract.admin().unwrap(), None);
+        assert_eq!(
+            contract.set_variables(Some(false), None, None, None, None, None, None),
+            Err(NFTCoreError::InvalidAccount)
+        );
+        assert_eq!(
+            contract.transfer_admin(pending_admin),
+            Err(NFTCoreError::InvalidAccount)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_grant_and_revoke_roles() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let minter = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+
+        assert!(contract.has_role(Role::Admin, installer).unwrap());
+        assert!(!contract.has_role(Role::Minter, minter).unwrap());
+
+        contract.grant_role(Role::Minter, minter).unwrap();
+        assert!(contract.has_role(Role::Minter, minter).unwrap());
+        assert!(!contract.has_role(Role::Admin, minter).unwrap());
+
+        contract.revoke_role(Role::Minter, minter).unwrap();
+        assert!(!contract.has_role(Role::Minter, minter).unwrap());
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_only_let_minters_mint() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let minter = Entity::Account([1; 32]);
+    let outsider = Entity::Account([2; 32]);
+
+    let mut contract = casper::native::dispatch_with(stub.clone(), || {
+        let mut contract = install_contract();
+        contract.grant_role(Role::Minter, minter).unwrap();
+        contract
+    })
+    .unwrap();
+
+    let result = casper::native::dispatch_with(stub.clone().with_caller(minter), || {
+        contract.mint("first".into(), minter, None).unwrap();
+        assert_eq!(contract.balance_of(minter).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+
+    let result = casper::native::dispatch_with(stub.with_caller(outsider), || {
+        assert_eq!(
+            contract.mint("second".into(), outsider, None),
+            Err(NFTCoreError::InvalidMinter)
+        );
+        assert_eq!(contract.balance_of(outsider).unwrap(), 0);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_let_metadata_updaters_update_any_token() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let updater = Entity::Account([1; 32]);
+
+    let (mut contract, minted_token) = casper::native::dispatch_with(stub.clone(), || {
+        let installer = casper::get_caller();
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .metadata_mutability(MetadataMutability::Mutable)
+                .build(),
+        );
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+        contract.grant_role(Role::MetadataUpdater, updater).unwrap();
+        (contract, minted_token)
+    })
+    .unwrap();
+
+    let result = casper::native::dispatch_with(stub.with_caller(updater), || {
+        contract
+            .set_token_metadata(minted_token.clone(), "updated".into())
+            .unwrap();
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_let_burners_burn_any_token() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let burner = Entity::Account([1; 32]);
+
+    let (mut contract, installer, minted_token) =
+        casper::native::dispatch_with(stub.clone(), || {
+            let installer = casper::get_caller();
+            let mut contract = install_contract();
+            let minted_token = contract.mint("first".into(), installer, None).unwrap();
+            contract.grant_role(Role::Burner, burner).unwrap();
+            (contract, installer, minted_token)
+        })
+        .unwrap();
+
+    let result = casper::native::dispatch_with(stub.with_caller(burner), || {
+        contract.burn(minted_token.clone()).unwrap();
+        assert_eq!(contract.balance_of(installer).unwrap(), 0);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_block_transfers_while_paused() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        contract.pause().unwrap();
+        assert_eq!(
+            contract.transfer(installer, recipient, minted_token.clone()),
+            Err(NFTCoreError::ContractIsPaused)
+        );
+
+        contract.unpause().unwrap();
+        contract
+            .transfer(installer, recipient, minted_token)
+            .unwrap();
+        assert_eq!(contract.balance_of(recipient).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_reject_transfer_of_locked_token() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        contract.lock_token(minted_token.clone()).unwrap();
+        assert!(contract.is_locked(minted_token.clone()).unwrap());
+        assert_eq!(
+            contract.transfer(installer, recipient, minted_token.clone()),
+            Err(NFTCoreError::TokenIsLocked)
+        );
+
+        contract.unlock_token(minted_token.clone()).unwrap();
+        contract
+            .transfer(installer, recipient, minted_token)
+            .unwrap();
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_not_batch_transfer_locked_token() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+
+        let first_token = contract.mint("first".into(), installer, None).unwrap();
+        let locked_token = contract.mint("second".into(), installer, None).unwrap();
+        contract.lock_token(locked_token.clone()).unwrap();
+
+        assert_eq!(
+            contract.batch_transfer(
+                installer,
+                recipient,
+                vec![first_token.clone(), locked_token]
+            ),
+            Err(NFTCoreError::TokenIsLocked)
+        );
+        assert_eq!(contract.balance_of(installer).unwrap(), 2);
+        assert_eq!(contract.balance_of(recipient).unwrap(), 0);
+        assert_eq!(contract.owner_of(first_token).unwrap(), installer);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_let_issuer_recover_soulbound_token() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let holder = Entity::Account([1; 32]);
+        let replacement = Entity::Account([2; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .ownership_mode(OwnershipMode::Soulbound)
+                .build(),
+        );
+        let minted_token = contract.mint("credential".into(), holder, None).unwrap();
+
+        contract
+            .recover_token(minted_token.clone(), replacement)
+            .unwrap();
+
+        assert_eq!(contract.owner_of(minted_token).unwrap(), replacement);
+        assert_eq!(contract.balance_of(holder).unwrap(), 0);
+        assert_eq!(contract.balance_of(replacement).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_not_recover_soulbound_token_while_paused_or_locked() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let holder = Entity::Account([1; 32]);
+        let replacement = Entity::Account([2; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .ownership_mode(OwnershipMode::Soulbound)
+                .build(),
+        );
+        let minted_token = contract.mint("credential".into(), holder, None).unwrap();
+
+        contract.pause().unwrap();
+        assert_eq!(
+            contract.recover_token(minted_token.clone(), replacement),
+            Err(NFTCoreError::ContractIsPaused)
+        );
+        contract.unpause().unwrap();
+
+        contract.lock_token(minted_token.clone()).unwrap();
+        assert_eq!(
+            contract.recover_token(minted_token.clone(), replacement),
+            Err(NFTCoreError::TokenIsLocked)
+        );
+        assert_eq!(contract.owner_of(minted_token).unwrap(), holder);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_record_minter_as_issuer() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let holder = Entity::Account([1; 32]);
+        let replacement = Entity::Account([2; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .ownership_mode(OwnershipMode::Soulbound)
+                .build(),
+        );
+
+        // Only the issuer can recover a token, so recovering tokens owned by `holder` shows that
+        // the caller, not the owner, was recorded as issuer.
+        let minted_token = contract.mint("first".into(), holder, None).unwrap();
+        let batch_minted_tokens = contract
+            .batch_mint(vec![MintEntry {
+                token_metadata: "second".into(),
+                token_owner: holder,
+                optional_token_hash: None,
+                royalty: None,
+            }])
+            .unwrap();
+
+        contract.recover_token(minted_token, replacement).unwrap();
+        contract
+            .recover_token(batch_minted_tokens[0].clone(), replacement)
+            .unwrap();
+        assert_eq!(contract.balance_of(replacement).unwrap(), 2);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_prefer_token_royalty_over_collection_default() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let collection_receiver = Entity::Account([1; 32]);
+        let token_receiver = Entity::Account([2; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .default_royalty(collection_receiver, 500)
+                .build(),
+        );
+
+        let default_token = contract.mint("first".into(), installer, None).unwrap();
+        let royalty = Royalty {
+            receiver: token_receiver,
+            basis_points: 1_000,
+        };
+        let custom_token = contract
+            .mint_with_royalty("second".into(), installer, None, Some(royalty))
+            .unwrap();
+
+        assert_eq!(
+            contract.royalty_info(default_token, 10_000).unwrap(),
+            Some(RoyaltyPayment {
+                receiver: collection_receiver,
+                amount: 500,
+            })
+        );
+        assert_eq!(
+            contract.royalty_info(custom_token, 10_000).unwrap(),
+            Some(RoyaltyPayment {
+                receiver: token_receiver,
+                amount: 1_000,
+            })
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_set_token_user() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let user = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+        assert_eq!(contract.user_of(minted_token.clone()).unwrap(), None);
+
+        let expires_at = casper::get_block_time() + 1_000;
+        contract
+            .set_user(minted_token.clone(), Some(user), expires_at)
+            .unwrap();
+        assert_eq!(contract.user_of(minted_token.clone()).unwrap(), Some(user));
+
+        contract.set_user(minted_token.clone(), None, 0).unwrap();
+        assert_eq!(contract.user_of(minted_token).unwrap(), None);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_not_report_expired_token_user() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let user = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        // The user is only valid strictly before `expires_at`.
+        let expires_at = casper::get_block_time();
+        contract
+            .set_user(minted_token.clone(), Some(user), expires_at)
+            .unwrap();
+        assert_eq!(contract.user_of(minted_token).unwrap(), None);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_clear_token_user_on_transfer() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let user = Entity::Account([1; 32]);
+        let recipient = Entity::Account([2; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        let expires_at = casper::get_block_time() + 1_000;
+        contract
+            .set_user(minted_token.clone(), Some(user), expires_at)
+            .unwrap();
+        contract
+            .transfer(installer, recipient, minted_token.clone())
+            .unwrap();
+
+        assert_eq!(contract.user_of(minted_token).unwrap(), None);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_approve_through_permit_once() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let public_key = signing_key.verifying_key().as_bytes().to_vec();
+    let owner = account_of(&signing_key);
+    let spender = Entity::Account([1; 32]);
+    let recipient = Entity::Account([2; 32]);
+
+    let (mut contract, minted_token) = casper::native::dispatch_with(stub.clone(), || {
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), owner, None).unwrap();
+
+        let deadline = casper::get_block_time() + 1_000;
+        let signature = sign_permit(&signing_key, spender, minted_token.clone(), deadline, 0);
+        contract
+            .permit(
+                owner,
+                spender,
+                minted_token.clone(),
+                deadline,
+                0,
+                public_key.clone(),
+                signature.clone(),
+            )
+            .unwrap();
+        assert_eq!(contract.permit_nonce(owner).unwrap(), 1);
+
+        // The nonce was consumed, so the same permit can't be used again.
+        assert_eq!(
+            contract.permit(
+                owner,
+                spender,
+                minted_token.clone(),
+                deadline,
+                0,
+                public_key,
+                signature
+            ),
+            Err(NFTCoreError::InvalidPermitNonce)
+        );
+        (contract, minted_token)
+    })
+    .unwrap();
+
+    // The permit approved `spender`, who can now move the token.
+    let result = casper::native::dispatch_with(stub.with_caller(spender), || {
+        contract
+            .transfer(owner, recipient, minted_token.clone())
+            .unwrap();
+        assert_eq!(contract.owner_of(minted_token).unwrap(), recipient);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_reject_permit_with_invalid_signature() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let other_key = SigningKey::from_bytes(&[8; 32]);
+        let owner = account_of(&signing_key);
+        let spender = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), owner, None).unwrap();
+
+        let deadline = casper::get_block_time() + 1_000;
+
+        // Signed by a key which doesn't belong to the owner.
+        let signature = sign_permit(&other_key, spender, minted_token.clone(), deadline, 0);
+        assert_eq!(
+            contract.permit(
+                owner,
+                spender,
+                minted_token.clone(),
+                deadline,
+                0,
+                other_key.verifying_key().as_bytes().to_vec(),
+                signature,
+            ),
+            Err(NFTCoreError::InvalidPermitSignature)
+        );
+
+        // Signed by the owner, but for another spender.
+        let signature = sign_permit(
+            &signing_key,
+            Entity::Account([2; 32]),
+            minted_token.clone(),
+            deadline,
+            0,
+        );
+        assert_eq!(
+            contract.permit(
+                owner,
+                spender,
+                minted_token,
+                deadline,
+                0,
+                signing_key.verifying_key().as_bytes().to_vec(),
+                signature,
+            ),
+            Err(NFTCoreError::InvalidPermitSignature)
+        );
+        assert_eq!(contract.permit_nonce(owner).unwrap(), 0);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_reject_permit_after_deadline() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS).with_block_time(10_000);
+    let result = casper::native::dispatch_with(stub, || {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let owner = account_of(&signing_key);
+        let spender = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), owner, None).unwrap();
+
+        let deadline = casper::get_block_time() - 1;
+        let signature = sign_permit(&signing_key, spender, minted_token.clone(), deadline, 0);
+        assert_eq!(
+            contract.permit(
+                owner,
+                spender,
+                minted_token,
+                deadline,
+                0,
+                signing_key.verifying_key().as_bytes().to_vec(),
+                signature,
+            ),
+            Err(NFTCoreError::PermitExpired)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_reject_approvals_expired_on_arrival() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS).with_block_time(1_000);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let spender = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        let now = casper::get_block_time();
+        assert_eq!(
+            contract.approve(None, spender, minted_token, Some(now)),
+            Err(NFTCoreError::InvalidExpiration)
+        );
+        assert_eq!(
+            contract.set_approval_for_all(true, spender, Some(now - 1)),
+            Err(NFTCoreError::InvalidExpiration)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_ignore_expired_approvals() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS).with_block_time(1_000);
+    let spender = Entity::Account([1; 32]);
+    let operator = Entity::Account([2; 32]);
+
+    let (mut contract, owner, minted_token) = casper::native::dispatch_with(stub.clone(), || {
+        let owner = casper::get_caller();
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), owner, None).unwrap();
+
+        // Both grants lapse at block time 2000.
+        contract
+            .approve(None, spender, minted_token.clone(), Some(2_000))
+            .unwrap();
+        contract
+            .set_approval_for_all(true, operator, Some(2_000))
+            .unwrap();
+        assert!(contract.is_approved_for_all(owner, operator).unwrap());
+
+        (contract, owner, minted_token)
+    })
+    .unwrap();
+
+    let later = stub.clone().with_block_time(2_000);
+    let result = casper::native::dispatch_with(later.clone(), || {
+        assert!(!contract.is_approved_for_all(owner, operator).unwrap());
+    });
+    assert!(result.is_ok());
+
+    let result = casper::native::dispatch_with(later.clone().with_caller(spender), || {
+        assert_eq!(
+            contract.transfer(owner, spender, minted_token.clone()),
+            Err(NFTCoreError::InvalidTokenOwner)
+        );
+    });
+    assert!(result.is_ok());
+
+    let result = casper::native::dispatch_with(later.with_caller(operator), || {
+        assert_eq!(
+            contract.transfer(owner, operator, minted_token.clone()),
+            Err(NFTCoreError::InvalidTokenOwner)
+        );
+        assert_eq!(
+            contract.burn(minted_token.clone()),
+            Err(NFTCoreError::InvalidTokenOwner)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_keep_several_approvals_until_transfer() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let first_marketplace = Entity::Contract([1; 32]);
+        let second_marketplace = Entity::Contract([2; 32]);
+        let recipient = Entity::Account([3; 32]);
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        contract
+            .approve(None, first_marketplace, minted_token.clone(), None)
+            .unwrap();
+        contract
+            .approve(None, second_marketplace, minted_token.clone(), None)
+            .unwrap();
+        assert_eq!(
+            contract.approved_spenders(minted_token.clone()).unwrap(),
+            vec![first_marketplace, second_marketplace]
+        );
+
+        contract
+            .revoke(minted_token.clone(), Some(first_marketplace))
+            .unwrap();
+        assert_eq!(
+            contract.approved_spenders(minted_token.clone()).unwrap(),
+            vec![second_marketplace]
+        );
+
+        contract
+            .transfer(installer, recipient, minted_token.clone())
+            .unwrap();
+        assert!(contract.approved_spenders(minted_token).unwrap().is_empty());
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_limit_approved_spenders_per_token() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS).with_block_time(1_000);
+    let spenders: Vec<Entity> = (1..=MAX_APPROVED_SPENDERS as u8)
+        .map(|index| Entity::Account([index; 32]))
+        .collect();
+    let late_spender = Entity::Account([0xff; 32]);
+
+    let (mut contract, minted_token) = casper::native::dispatch_with(stub.clone(), || {
+        let installer = casper::get_caller();
+
+        let mut contract = install_contract();
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+
+        // Only the first approval lapses, at block time 2000.
+        for (index, spender) in spenders.iter().enumerate() {
+            let expires_at = if index == 0 { Some(2_000) } else { None };
+            contract
+                .approve(None, *spender, minted_token.clone(), expires_at)
+                .unwrap();
+        }
+        assert_eq!(
+            contract.approve(None, late_spender, minted_token.clone(), None),
+            Err(NFTCoreError::TooManyApprovedSpenders)
+        );
+
+        (contract, minted_token)
+    })
+    .unwrap();
+
+    let result = casper::native::dispatch_with(stub.with_block_time(2_000), || {
+        contract
+            .approve(None, late_spender, minted_token.clone(), None)
+            .unwrap();
+
+        let approved_spenders = contract.approved_spenders(minted_token.clone()).unwrap();
+        assert_eq!(approved_spenders.len(), MAX_APPROVED_SPENDERS);
+        assert!(!approved_spenders.contains(&spenders[0]));
+        assert!(approved_spenders.contains(&late_spender));
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_reject_malformed_token_hash() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .identifier_mode(NFTIdentifierMode::Hash)
+                .build(),
+        );
+
+        assert_eq!(
+            contract.mint("first".into(), installer, Some("NOT-A-HASH".into())),
+            Err(NFTCoreError::InvalidIdentifier)
+        );
+
+        // Surrounding whitespace doesn't change the identity of the token.
+        contract.mint("second".into(), installer, None).unwrap();
+        assert_eq!(
+            contract.mint(" second\n".into(), installer, None),
+            Err(NFTCoreError::DuplicateIdentifier)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_generate_sha256_token_hashes() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .identifier_mode(NFTIdentifierMode::Hash)
+                .hash_algorithm(HashAlgorithm::Sha256)
+                .build(),
+        );
+
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+        assert_eq!(
+            minted_token,
+            TokenIdentifier::Hash(base16::encode_lower(&Sha256::digest(b"first")))
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_resolve_ordinals_in_hash_mode() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .identifier_mode(NFTIdentifierMode::Hash)
+                .build(),
+        );
+
+        let minted_token = contract.mint("first".into(), installer, None).unwrap();
+        let TokenIdentifier::Hash(hash) = minted_token else {
+            panic!("expected a hash identifier");
+        };
+
+        assert_eq!(contract.token_hash_by_index(0).unwrap(), hash);
+        assert_eq!(contract.token_index_by_hash(hash).unwrap(), 0);
+
+        contract
+            .transfer(installer, recipient, TokenIdentifier::Ordinal(0))
+            .unwrap();
+        assert_eq!(
+            contract.owner_of(TokenIdentifier::Ordinal(0)).unwrap(),
+            recipient
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_reject_invalid_token_identifiers_consistently() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let recipient = Entity::Account([1; 32]);
+
+        let mut contract = install_contract();
+        contract.mint("first".into(), installer, None).unwrap();
+
+        let out_of_range = TokenIdentifier::Ordinal(1);
+        let wrong_variant = TokenIdentifier::Hash("0".repeat(64));
+
+        assert_eq!(
+            contract.transfer(installer, recipient, out_of_range.clone()),
+            Err(NFTCoreError::InvalidTokenIdentifier)
+        );
+        assert_eq!(
+            contract.burn(out_of_range),
+            Err(NFTCoreError::InvalidTokenIdentifier)
+        );
+        assert_eq!(
+            contract.owner_of(wrong_variant),
+            Err(NFTCoreError::InvalidTokenIdentifier)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_migrate_tokens_until_finalized() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let holder = Entity::Account([1; 32]);
+
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .allow_migration(true)
+                .build(),
+        );
+
+        let records = vec![
+            MigrationRecord {
+                token_identifier: TokenIdentifier::Ordinal(0),
+                owner: Some(holder),
+                issuer: Some(installer),
+                metadata: "first".into(),
+                approved: None,
+                burned: false,
+            },
+            MigrationRecord {
+                token_identifier: TokenIdentifier::Ordinal(1),
+                owner: Some(holder),
+                issuer: Some(installer),
+                metadata: "second".into(),
+                approved: None,
+                burned: true,
+            },
+        ];
+        contract.migrate(records).unwrap();
+
+        assert_eq!(contract.balance_of(holder).unwrap(), 1);
+        assert_eq!(contract.number_of_minted_tokens().unwrap(), 2);
+        assert_eq!(
+            contract.mint("third".into(), installer, None),
+            Err(NFTCoreError::MintingIsPaused)
+        );
+
+        contract.finalize_migration().unwrap();
+        assert_eq!(
+            contract.migrate(Vec::new()),
+            Err(NFTCoreError::ContractAlreadyMigrated)
+        );
+        contract.mint("third".into(), installer, None).unwrap();
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_upgrade_and_lower_total_supply() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        casper::write_state(&install_contract()).unwrap();
+        assert_eq!(
+            NFTContract::upgrade(None),
+            Err(NFTCoreError::MissingPackageHashForUpgrade)
+        );
+
+        let contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .package_hash("package")
+                .build(),
+        );
+        casper::write_state(&contract).unwrap();
+        assert_eq!(
+            NFTContract::upgrade(Some(101)),
+            Err(NFTCoreError::CannotUpgradeToMoreSupply)
+        );
+
+        NFTContract::upgrade(Some(50)).unwrap();
+        let contract = casper::read_state::<NFTContract>().unwrap();
+        assert_eq!(contract.state_version().unwrap(), STATE_VERSION);
+        assert_eq!(contract.total_supply().unwrap(), Some(50));
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_upgrade_state_with_baseline_layout() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let installer = casper::get_caller();
+        let holder = Entity::Account([1; 32]);
+
+        // State, token and balance as written before the state was versioned.
+        let baseline_state = BaselineState {
+            collection_name: "baseline-collection".into(),
+            collection_symbol: "bc".into(),
+            total_token_supply: 100,
+            allow_minting: true,
+            minting_mode: MintingMode::Installer,
+            ownership_mode: OwnershipMode::Transferable,
+            nft_kind: NFTKind::Virtual,
+            whitelist_mode: WhitelistMode::Unlocked,
+            acl_whitelist: Vec::new(),
+            acl_package_mode: false,
+            package_operator_mode: false,
+            package_hash: "package".into(),
+            base_metadata_kind: NFTMetadataKind::Raw,
+            optional_metadata: Vec::new(),
+            additional_required_metadata: Vec::new(),
+            identifier_mode: NFTIdentifierMode::Ordinal,
+            metadata_mutability: MetadataMutability::Immutable,
+            installer,
+            events_mode: EventsMode::NoEvents,
+            minted_tokens_count: 1,
+            burn_mode: BurnMode::Burnable,
+            operator_burn_mode: false,
+            store: BaselineStateStore {
+                operators: Vec::new(),
+                entity_data: Map::new("ENTITY_DATA"),
+                data: Map::new("TOKEN_DATA"),
+                hash_by_index: Map::new("STORE_HASH_BY_INDEX"),
+                index_by_hash: Map::new("STORE_INDEX_BY_HASH"),
+                burned_tokens: Vec::new(),
+                json_schema: None,
+                metadata: Map::new("STORE_METADATA"),
+            },
+        };
+        casper::write_state(&baseline_state).unwrap();
+
+        let mut token_data = Map::<TokenIdentifier, BaselineTokenData>::new("TOKEN_DATA");
+        token_data.insert(
+            &TokenIdentifier::Ordinal(0),
+            &BaselineTokenData {
+                approved: None,
+                issuer: Some(installer),
+                owner: Some(holder),
+                metadata: "first".into(),
+            },
+        );
+        let mut entity_data = Map::<Entity, BaselineEntityData>::new("ENTITY_DATA");
+        entity_data.insert(
+            &holder,
+            &BaselineEntityData {
+                balance: 1,
+                whitelisted: false,
+            },
+        );
+
+        NFTContract::upgrade(None).unwrap();
+
+        let contract = casper::read_state::<NFTContract>().unwrap();
+        assert_eq!(contract.state_version().unwrap(), STATE_VERSION);
+        assert_eq!(contract.total_supply().unwrap(), Some(100));
+        assert_eq!(contract.collection_name().unwrap(), "baseline-collection");
+        assert_eq!(
+            contract.owner_of(TokenIdentifier::Ordinal(0)).unwrap(),
+            holder
+        );
+        assert_eq!(contract.balance_of(holder).unwrap(), 1);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_reject_upgrade_of_unknown_state_version() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        // Neither the current layout nor the baseline one.
+        casper::write_state(&(STATE_VERSION + 1)).unwrap();
+        assert_eq!(
+            NFTContract::upgrade(None),
+            Err(NFTCoreError::InvalidCheckForUpgrade)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_only_decrease_total_supply() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let owner = casper::get_caller();
+        let mut contract = install_contract();
+        for index in 0..3 {
+            contract
+                .mint(format!("token-{index}"), owner, None)
+                .unwrap();
+        }
+
+        assert_eq!(
+            contract.set_variables(None, None, None, None, None, None, Some(2)),
+            Err(NFTCoreError::InvalidTotalTokenSupply)
+        );
+        contract
+            .set_variables(None, None, None, None, None, None, Some(3))
+            .unwrap();
+        assert_eq!(contract.total_supply().unwrap(), Some(3));
+        assert_eq!(
+            contract.set_variables(None, None, None, None, None, None, Some(4)),
+            Err(NFTCoreError::CannotUpgradeToMoreSupply)
+        );
+        assert_eq!(
+            contract.mint("token-3".into(), owner, None),
+            Err(NFTCoreError::TokenSupplyDepleted)
+        );
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn should_mint_without_supply_cap() {
+    let stub = Environment::new(Default::default(), DEFAULT_ADDRESS);
+    let result = casper::native::dispatch_with(stub, || {
+        let owner = casper::get_caller();
+        let mut contract = NFTContract::new(
+            InstallConfig::builder()
+                .base_metadata_kind(NFTMetadataKind::Raw)
+                .total_token_supply(1)
+                .unlimited_supply()
+                .build(),
+        );
+
+        contract.mint("first".into(), owner, None).unwrap();
+        contract.mint("second".into(), owner, None).unwrap();
+        assert_eq!(contract.total_supply().unwrap(), None);
+        assert_eq!(contract.collection_info().unwrap().total_token_supply, None);
+
+        contract
+            .set_variables(None, None, None, None, None, None, Some(2))
+            .unwrap();
+        assert_eq!(contract.total_supply().unwrap(), Some(2));
+    });
+    assert!(result.is_ok());
+}
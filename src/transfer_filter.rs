@@ -0,0 +1,16 @@
+use casper_macros::casper;
+use casper_sdk::casper::Entity;
+
+use crate::types::{TokenIdentifier, TransferFilterContractResult};
+
+/// Interface of the optional transfer filter contract configured at install. It is consulted on
+/// every transfer and can deny it, e.g. to enforce royalties or compliance rules.
+#[casper(trait_definition)]
+pub trait CEP78TransferFilter {
+    fn can_transfer(
+        &self,
+        source_owner: Entity,
+        target_owner: Entity,
+        token_id: TokenIdentifier,
+    ) -> TransferFilterContractResult;
+}
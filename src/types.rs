@@ -47,20 +47,144 @@ pub(crate) struct CustomMetadata {
 pub struct OperatorEntry {
     pub key: Entity,
     pub value: Entity,
+    pub expires_at: Option<u64>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, CasperABI, Default, Debug, Clone)]
-pub struct TokenData {
+/// Current layout version of `CEP78State`. Bump it whenever the layout changes, keep the
+/// previous layout around and teach `StoredState` to decode and convert it.
+pub const STATE_VERSION: u32 = 1;
+
+/// Royalties are expressed in basis points of the sale price, so this is 100%.
+pub const MAX_ROYALTY_BASIS_POINTS: u16 = 10_000;
+
+// Royalty owed to `receiver` on every sale, in basis points of the sale price.
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
+pub struct Royalty {
+    pub receiver: Entity,
+    pub basis_points: u16,
+}
+
+/// Domain tag of every `PermitMessage`, so that permit signatures can't be replayed as
+/// signatures over anything else.
+pub const PERMIT_DOMAIN: &str = "CEP78_PERMIT";
+
+// The message an owner signs off-chain to approve `spender` through `permit`. The signature
+// covers the borsh serialization of this structure. `contract` is the address of the collection
+// contract itself, so that a permit can't be replayed on another collection.
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone)]
+pub struct PermitMessage {
+    pub domain: String,
+    pub contract: Entity,
+    pub package_hash: String,
+    pub chain_name: String,
+    pub owner: Entity,
+    pub spender: Entity,
+    pub token_identifier: TokenIdentifier,
+    pub deadline: u64,
+    pub nonce: u64,
+}
+
+// Entity allowed to use a token without owning it, until `expires_at` (block time).
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
+pub struct TokenUser {
+    pub user: Entity,
+    pub expires_at: u64,
+}
+
+// Answer to `royalty_info` for a given sale price.
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
+pub struct RoyaltyPayment {
+    pub receiver: Entity,
+    pub amount: u64,
+}
+
+/// How many accounts can be approved for a single token at the same time.
+pub const MAX_APPROVED_SPENDERS: usize = 8;
+
+// An account approved to transfer a token, optionally until `expires_at` (block time).
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone)]
+pub struct ApprovedSpender {
+    pub spender: Entity,
+    pub expires_at: Option<u64>,
+}
+
+// A token exported from a VM1 CEP-78 contract, imported through `migrate`.
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone)]
+pub struct MigrationRecord {
+    pub token_identifier: TokenIdentifier,
+    pub owner: Option<Entity>,
+    pub issuer: Option<Entity>,
+    pub metadata: String,
     pub approved: Option<Entity>,
+    pub burned: bool,
+}
+
+// A single token to be minted by `batch_mint`.
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone)]
+pub struct MintEntry {
+    pub token_metadata: String,
+    pub token_owner: Entity,
+    pub optional_token_hash: Option<String>,
+    pub royalty: Option<Royalty>,
+}
+
+#[derive(BorshSerialize, CasperABI, Default, Debug, Clone)]
+pub struct TokenData {
+    pub approved: Vec<ApprovedSpender>,
     pub issuer: Option<Entity>,
     pub owner: Option<Entity>,
     pub metadata: String,
+    pub locked: bool,
+    pub royalty: Option<Royalty>,
+    pub user: Option<TokenUser>,
+}
+
+// Tokens minted before the state was versioned are still stored in the baseline layout, they
+// are converted the first time they are read. Only ever decoded as a standalone map value.
+impl BorshDeserialize for TokenData {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        match borsh::from_slice(&bytes) {
+            Ok((approved, issuer, owner, metadata, locked, royalty, user)) => Ok(Self {
+                approved,
+                issuer,
+                owner,
+                metadata,
+                locked,
+                royalty,
+                user,
+            }),
+            Err(_) => borsh::from_slice::<BaselineTokenData>(&bytes).map(Self::from),
+        }
+    }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, CasperABI, Default, Debug, Clone)]
+#[derive(BorshSerialize, CasperABI, Default, Debug, Clone)]
 pub struct EntityData {
     pub balance: u64,
     pub whitelisted: bool,
+    pub roles: Vec<Role>,
+    pub permit_nonce: u64,
+}
+
+// Same as `TokenData`, entities written before the state was versioned are converted on read.
+impl BorshDeserialize for EntityData {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        match borsh::from_slice(&bytes) {
+            Ok((balance, whitelisted, roles, permit_nonce)) => Ok(Self {
+                balance,
+                whitelisted,
+                roles,
+                permit_nonce,
+            }),
+            Err(_) => borsh::from_slice::<BaselineEntityData>(&bytes).map(Self::from),
+        }
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone)]
@@ -101,6 +225,50 @@ impl Default for StateStore {
 
 #[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone)]
 pub struct CEP78State {
+    // Layout version of this structure, see `STATE_VERSION`. It has to stay the first field of
+    // every versioned layout, `StoredState` relies on it to pick the layout to decode.
+    pub version: u32,
+    pub collection_name: String,
+    pub collection_symbol: String,
+    // `None` for open editions without a supply cap.
+    pub total_token_supply: Option<u64>,
+    pub allow_minting: bool,
+    pub minting_mode: MintingMode,
+    pub ownership_mode: OwnershipMode,
+    pub nft_kind: NFTKind,
+    pub whitelist_mode: WhitelistMode,
+    pub acl_whitelist: Vec<Entity>,
+    pub acl_package_mode: bool,
+    pub package_operator_mode: bool,
+    pub package_hash: String,
+    pub base_metadata_kind: NFTMetadataKind,
+    pub optional_metadata: Vec<u8>,
+    pub additional_required_metadata: Vec<u8>,
+    pub identifier_mode: NFTIdentifierMode,
+    pub metadata_mutability: MetadataMutability,
+
+    pub installer: Option<Entity>,
+    pub pending_installer: Option<Entity>,
+    pub paused: bool,
+    pub events_mode: EventsMode,
+    pub minted_tokens_count: u64,
+    pub burn_mode: BurnMode,
+    pub operator_burn_mode: bool,
+    pub transfer_filter_contract: Option<Entity>,
+    pub token_lock_mode: TokenLockMode,
+    pub default_royalty: Option<Royalty>,
+    pub chain_name: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub allow_migration: bool,
+    pub migration_finalized: bool,
+
+    pub store: StateStore,
+}
+
+// Layout of `CEP78State` written by releases before the state was versioned. It has no leading
+// version, `StoredState` tells it apart by the current layout failing to decode.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BaselineState {
     pub collection_name: String,
     pub collection_symbol: String,
     pub total_token_supply: u64,
@@ -125,7 +293,177 @@ pub struct CEP78State {
     pub burn_mode: BurnMode,
     pub operator_burn_mode: bool,
 
-    pub store: StateStore,
+    pub store: BaselineStateStore,
+}
+
+// The maps only store their names, so the baseline store shares them with the current one.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BaselineStateStore {
+    pub operators: Vec<BaselineOperatorEntry>,
+    pub entity_data: Map<Entity, EntityData>,
+    pub data: Map<TokenIdentifier, TokenData>,
+    pub hash_by_index: Map<u64, String>,
+    pub index_by_hash: Map<String, u64>,
+    pub burned_tokens: Vec<TokenIdentifier>,
+    pub json_schema: Option<String>,
+    pub metadata: Map<TokenIdentifier, String>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BaselineOperatorEntry {
+    pub key: Entity,
+    pub value: Entity,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BaselineTokenData {
+    pub approved: Option<Entity>,
+    pub issuer: Option<Entity>,
+    pub owner: Option<Entity>,
+    pub metadata: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BaselineEntityData {
+    pub balance: u64,
+    pub whitelisted: bool,
+}
+
+impl From<BaselineState> for CEP78State {
+    fn from(state: BaselineState) -> Self {
+        let store = StateStore {
+            operators: state
+                .store
+                .operators
+                .into_iter()
+                .map(|entry| OperatorEntry {
+                    key: entry.key,
+                    value: entry.value,
+                    expires_at: None,
+                })
+                .collect(),
+            entity_data: state.store.entity_data,
+            data: state.store.data,
+            hash_by_index: state.store.hash_by_index,
+            index_by_hash: state.store.index_by_hash,
+            burned_tokens: state.store.burned_tokens,
+            json_schema: state.store.json_schema,
+            metadata: state.store.metadata,
+        };
+
+        Self {
+            version: STATE_VERSION,
+            collection_name: state.collection_name,
+            collection_symbol: state.collection_symbol,
+            total_token_supply: Some(state.total_token_supply),
+            allow_minting: state.allow_minting,
+            minting_mode: state.minting_mode,
+            ownership_mode: state.ownership_mode,
+            nft_kind: state.nft_kind,
+            whitelist_mode: state.whitelist_mode,
+            // The baseline never applied its install time whitelist, minting rights only ever
+            // lived in the entity data. Dropping the list keeps it from granting new rights.
+            acl_whitelist: Vec::new(),
+            acl_package_mode: state.acl_package_mode,
+            package_operator_mode: state.package_operator_mode,
+            package_hash: state.package_hash,
+            base_metadata_kind: state.base_metadata_kind,
+            optional_metadata: state.optional_metadata,
+            additional_required_metadata: state.additional_required_metadata,
+            identifier_mode: state.identifier_mode,
+            metadata_mutability: state.metadata_mutability,
+            installer: Some(state.installer),
+            pending_installer: None,
+            paused: false,
+            events_mode: state.events_mode,
+            minted_tokens_count: state.minted_tokens_count,
+            burn_mode: state.burn_mode,
+            operator_burn_mode: state.operator_burn_mode,
+            transfer_filter_contract: None,
+            token_lock_mode: TokenLockMode::Admin,
+            default_royalty: None,
+            chain_name: String::new(),
+            hash_algorithm: HashAlgorithm::Blake2b256,
+            allow_migration: false,
+            migration_finalized: false,
+            store,
+        }
+    }
+}
+
+impl From<BaselineTokenData> for TokenData {
+    fn from(data: BaselineTokenData) -> Self {
+        Self {
+            approved: data
+                .approved
+                .into_iter()
+                .map(|spender| ApprovedSpender {
+                    spender,
+                    expires_at: None,
+                })
+                .collect(),
+            issuer: data.issuer,
+            owner: data.owner,
+            metadata: data.metadata,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<BaselineEntityData> for EntityData {
+    fn from(data: BaselineEntityData) -> Self {
+        Self {
+            balance: data.balance,
+            whitelisted: data.whitelisted,
+            ..Default::default()
+        }
+    }
+}
+
+// Contract state as found in storage by `upgrade`, in whatever layout wrote it. Versioned
+// layouts start with their version, the baseline layout is whatever fails to decode as the
+// current one.
+pub enum StoredState {
+    Baseline(BaselineState),
+    Current(CEP78State),
+}
+
+impl BorshDeserialize for StoredState {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let version = u32::deserialize(&mut bytes.as_slice())?;
+        if version == STATE_VERSION {
+            if let Ok(state) = borsh::from_slice(&bytes) {
+                return Ok(Self::Current(state));
+            }
+        }
+
+        borsh::from_slice(&bytes).map(Self::Baseline).map_err(|_| {
+            borsh::io::Error::new(borsh::io::ErrorKind::InvalidData, "unknown state layout")
+        })
+    }
+}
+
+// Summary of the collection configuration returned by `collection_info`.
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
+pub struct CollectionInfo {
+    pub collection_name: String,
+    pub collection_symbol: String,
+    // `None` for open editions without a supply cap.
+    pub total_token_supply: Option<u64>,
+    pub minted_tokens_count: u64,
+    pub circulating_supply: u64,
+    pub allow_minting: bool,
+    pub minting_mode: MintingMode,
+    pub ownership_mode: OwnershipMode,
+    pub nft_kind: NFTKind,
+    pub base_metadata_kind: NFTMetadataKind,
+    pub metadata_mutability: MetadataMutability,
+    pub burn_mode: BurnMode,
+    pub identifier_mode: NFTIdentifierMode,
+    pub events_mode: EventsMode,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
@@ -135,6 +473,16 @@ pub enum NFTIdentifierMode {
     Hash = 1,
 }
 
+/// Length of a token hash in hash identifier mode, i.e. a hex encoded 32 byte digest.
+pub const TOKEN_HASH_HEX_LENGTH: usize = 64;
+
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
+#[borsh(use_discriminant = true)]
+pub enum HashAlgorithm {
+    Blake2b256 = 0,
+    Sha256 = 1,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
 #[borsh(use_discriminant = true)]
 pub enum EventsMode {
@@ -193,6 +541,9 @@ pub enum OwnershipMode {
     Assigned = 1,
     /// The NFT can be transferred even to an recipient that does not exist.
     Transferable = 2,
+    /// The NFT can't be transferred by its holder, but its issuer can move it to a replacement
+    /// address through `recover_token`.
+    Soulbound = 3,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
@@ -225,6 +576,37 @@ pub enum WhitelistMode {
     Locked = 1,
 }
 
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, Copy, PartialEq)]
+#[borsh(use_discriminant = true)]
+pub enum Role {
+    /// Can change the contract variables and grant or revoke roles. Implies every other role.
+    Admin = 0,
+    /// Can mint when the minting mode is `Installer`.
+    Minter = 1,
+    /// Can update the metadata of any token when metadata is mutable.
+    MetadataUpdater = 2,
+    /// Can pause and unpause the contract.
+    Pauser = 3,
+    /// Can burn any token, regardless of its owner.
+    Burner = 4,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
+#[borsh(use_discriminant = true)]
+pub enum TokenLockMode {
+    /// Only admins can lock and unlock tokens.
+    Admin = 0,
+    /// Token owners can lock and unlock their own tokens as well.
+    AdminAndOwner = 1,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
+#[borsh(use_discriminant = true)]
+pub enum TransferFilterContractResult {
+    DenyTransfer = 0,
+    ProceedTransfer = 1,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, CasperABI, Debug, Clone, PartialEq)]
 pub enum TokenIdentifier {
     Ordinal(u64),